@@ -0,0 +1,128 @@
+// Function-call actions carried by executable proposals, run once they pass.
+// Consecutive actions on the same receiver run as one batch, which succeeds
+// or fails as a whole.
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{Base64VecU8, U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, log, near_bindgen, AccountId, Gas, Promise, PromiseResult};
+
+use crate::{Contract, ContractExt, ProposalKind, ProposalStatus};
+
+// Gas for recording the outcome of each batch of actions
+pub(crate) const GAS_FOR_ON_ACTIONS_EXECUTED: Gas = Gas(5_000_000_000_000);
+
+// The most actions a proposal may carry, and the most gas they may use together
+const MAX_ACTIONS: usize = 10;
+const MAX_ACTIONS_GAS: Gas = Gas(200_000_000_000_000);
+
+// A function call made by the contract once the proposal passes
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProposalAction {
+    pub receiver_id: AccountId,
+    pub method_name: String,
+    pub args: Base64VecU8,
+    // Attached from the treasury's NEAR
+    pub deposit: U128,
+    pub gas: U64,
+}
+
+// How an action of a passed proposal went
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum ActionStatus {
+    Pending,
+    Succeeded,
+    Failed,
+}
+
+// Panics unless the actions fit in one execution of a passed proposal
+pub(crate) fn validate_actions(actions: &[ProposalAction]) {
+    assert!(
+        !actions.is_empty() && actions.len() <= MAX_ACTIONS,
+        "A Proposal with Actions needs 1 to {} of them", MAX_ACTIONS
+    );
+    let mut total_gas = 0;
+    for action in actions {
+        assert!(action.gas.0 > 0, "Every Action needs Gas");
+        total_gas += action.gas.0 + GAS_FOR_ON_ACTIONS_EXECUTED.0;
+    }
+    assert!(total_gas <= MAX_ACTIONS_GAS.0, "Actions need more than {} Gas", MAX_ACTIONS_GAS.0);
+}
+
+#[near_bindgen]
+impl Contract {
+    // Public method - get how each action of a passed proposal went, in order
+    pub fn get_action_statuses(&self, proposal_id: u128) -> Option<Vec<ActionStatus>> {
+        self.action_statuses.get(&proposal_id).cloned()
+    }
+
+    // Callback - records whether a batch of actions of a passed proposal
+    // succeeded, returning the deposits of a failed one to the treasury. The
+    // proposal counts as executed once every action has run, except for a
+    // failed upgrade, which stays passed so it can be retried.
+    #[private]
+    pub fn on_actions_executed(&mut self, proposal_id: u128, first_action: u32, action_count: u32) -> ActionStatus {
+        let status = match env::promise_result(0) {
+            PromiseResult::Successful(_) => ActionStatus::Succeeded,
+            _ => ActionStatus::Failed,
+        };
+        let batch = first_action as usize..(first_action + action_count) as usize;
+        log!("Actions {} to {} of Proposal {} {:?}", batch.start, batch.end - 1, proposal_id, status);
+        if let (ActionStatus::Failed, ProposalKind::Actions { actions }) = (status, &self.proposal_kinds[&proposal_id]) {
+            // the runtime refunds the deposits of a failed batch to the contract
+            let deposits = actions[batch.clone()].iter().map(|action| action.deposit.0).sum();
+            self.add_to_treasury(deposits);
+        }
+        let statuses = self.action_statuses.get_mut(&proposal_id).unwrap();
+        statuses[batch].fill(status);
+        let upgrade_failed = status == ActionStatus::Failed
+            && matches!(self.proposal_kinds[&proposal_id], ProposalKind::Upgrade { .. });
+        if !statuses.contains(&ActionStatus::Pending) && !upgrade_failed {
+            self.set_status(proposal_id, ProposalStatus::Executed);
+        }
+        status
+    }
+}
+
+// Internal helpers, not exposed as contract methods
+impl Contract {
+    // Panics unless the proposer may propose the actions. Only the admin and
+    // the council may propose actions calling the contract itself, as those
+    // act with the admin's rights.
+    pub(crate) fn assert_actions_proposable(&self, proposer: &AccountId, actions: &[ProposalAction]) {
+        let calls_itself = actions.iter().any(|action| action.receiver_id == env::current_account_id());
+        assert!(
+            !calls_itself || self.is_council_or_admin(proposer),
+            "Only the Admin or the Council can Propose Actions on the Contract"
+        );
+    }
+
+    // Runs each run of consecutive actions on the same receiver as one batch
+    // of function calls, followed by a callback recording its outcome, so a
+    // failing batch does not stop the others
+    pub(crate) fn execute_actions(&mut self, proposal_id: u128, actions: &[ProposalAction]) {
+        log!("Executing {} Actions of Proposal {}", actions.len(), proposal_id);
+        self.action_statuses.insert(proposal_id, vec![ActionStatus::Pending; actions.len()]);
+        self.spend_action_deposits(proposal_id);
+        let mut first_action = 0;
+        for batch in actions.chunk_by(|action, next| action.receiver_id == next.receiver_id) {
+            let mut promise = Promise::new(batch[0].receiver_id.clone());
+            for action in batch {
+                promise = promise.function_call(
+                    action.method_name.clone(),
+                    action.args.0.clone(),
+                    action.deposit.0,
+                    Gas(action.gas.0),
+                );
+            }
+            promise.then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_ON_ACTIONS_EXECUTED)
+                    .on_actions_executed(proposal_id, first_action, batch.len() as u32),
+            );
+            first_action += batch.len() as u32;
+        }
+    }
+}
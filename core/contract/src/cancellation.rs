@@ -0,0 +1,77 @@
+// Owners withdrawing their own proposals. Until the first ballot a proposal
+// can always be cancelled; after that it takes the admin's approval, or
+// approvals from enough council members.
+use near_sdk::json_types::U128;
+use near_sdk::{env, log, near_bindgen, AccountId};
+
+use crate::events::{Event, ProposalCancelled};
+use crate::{BondStatus, Contract, ContractExt, ProposalStatus, Role};
+
+#[near_bindgen]
+impl Contract {
+    // Public method - get how many council members must approve cancelling a proposal with ballots
+    pub fn get_cancel_approvals_needed(&self) -> u32 {
+        self.cancel_approvals_needed
+    }
+
+    // Public method - get who approved cancelling a proposal
+    pub fn get_cancel_approvals(&self, proposal_id: u128) -> Vec<AccountId> {
+        self.cancel_approvals.get(&proposal_id).cloned().unwrap_or_default()
+    }
+
+    // Public method - sets how many council members must approve cancelling a proposal with ballots
+    pub fn set_cancel_approvals_needed(&mut self, approvals: u32) {
+        self.assert_admin();
+        assert!(approvals > 0, "At least One Approval is Needed");
+        self.cancel_approvals_needed = approvals;
+    }
+
+    // Public method - lets the admin or a council member approve cancelling a proposal
+    pub fn approve_cancellation(&mut self, proposal_id: u128) {
+        assert!(!self.status_of(proposal_id).is_closed(), "Proposal has Already Closed!");
+        let approver = env::predecessor_account_id();
+        assert!(
+            self.is_council_or_admin(&approver),
+            "Only the Admin or the Council can Approve a Cancellation"
+        );
+        let approvals = self.cancel_approvals.entry(proposal_id).or_default();
+        assert!(!approvals.contains(&approver), "Cancellation is Already Approved");
+        log!("{} Approves Cancelling Proposal {}", approver, proposal_id);
+        approvals.push(approver);
+    }
+
+    // Public method - lets the owner withdraw a draft or open proposal and get
+    // its bond back. Once ballots exist the cancellation must be approved.
+    pub fn cancel_proposal(&mut self, proposal_id: u128) {
+        assert!(!self.status_of(proposal_id).is_closed(), "Proposal has Already Closed!");
+        let caller = env::predecessor_account_id();
+        assert_eq!(&caller, &self.proposals[&proposal_id].owner);
+        assert!(
+            !self.has_ballots(proposal_id) || self.cancellation_approved(proposal_id),
+            "A Proposal with Ballots needs Admin or Council Approval to be Cancelled"
+        );
+        self.set_status(proposal_id, ProposalStatus::Cancelled);
+        self.cancelled_proposal_count += 1;
+        self.cancel_approvals.remove(&proposal_id);
+        self.settle_bond(proposal_id, BondStatus::Refunded);
+        self.release_commitments(proposal_id);
+        Event::ProposalCancelled(&[ProposalCancelled { proposal_id: U128(proposal_id), cancelled_by: &caller }])
+            .emit();
+    }
+}
+
+// Internal helpers, not exposed as contract methods
+impl Contract {
+    // Whether the admin, or enough current council members, approved cancelling the proposal
+    fn cancellation_approved(&self, proposal_id: u128) -> bool {
+        let Some(approvals) = self.cancel_approvals.get(&proposal_id) else { return false };
+        if approvals.iter().any(|approver| self.is_admin(approver)) {
+            return true;
+        }
+        let council_approvals = approvals
+            .iter()
+            .filter(|approver| self.members.get(*approver) == Some(&Role::Council))
+            .count();
+        council_approvals >= self.cancel_approvals_needed as usize
+    }
+}
@@ -0,0 +1,123 @@
+// NEP-297 events, logged as EVENT_JSON:{"standard":...,"version":...,"event":...,"data":[...]}
+// so indexers can follow proposals and ballots without parsing free text
+use near_sdk::json_types::U128;
+use near_sdk::serde::Serialize;
+use near_sdk::{log, serde_json, AccountId};
+
+use crate::{Ballot, ProposalKind, ProposalOutcome, Vote};
+
+const EVENT_STANDARD: &str = "nvoter";
+const EVENT_VERSION: &str = "1.0.0";
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "event", content = "data")]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Event<'a> {
+    ProposalCreated(&'a [ProposalCreated<'a>]),
+    VoteCast(&'a [VoteCast<'a>]),
+    VoteChanged(&'a [VoteChanged<'a>]),
+    ProposalClosed(&'a [ProposalClosed<'a>]),
+    ProposalVoided(&'a [ProposalVoided<'a>]),
+    ProposalCancelled(&'a [ProposalCancelled<'a>]),
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub(crate) struct ProposalCreated<'a> {
+    pub proposal_id: U128,
+    pub owner_id: &'a AccountId,
+    pub title: &'a str,
+    pub kind: &'a ProposalKind,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub(crate) struct VoteCast<'a> {
+    pub proposal_id: U128,
+    pub voter_id: &'a AccountId,
+    pub ballot: &'a Ballot,
+    pub weight: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub(crate) struct VoteChanged<'a> {
+    pub proposal_id: U128,
+    pub voter_id: &'a AccountId,
+    pub old_ballot: &'a Ballot,
+    pub new_ballot: &'a Ballot,
+    pub weight: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub(crate) struct ProposalClosed<'a> {
+    pub proposal_id: U128,
+    pub outcome: ProposalOutcome,
+    pub tally: &'a [U128],
+    pub winning_option: Option<u32>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub(crate) struct ProposalVoided<'a> {
+    pub proposal_id: U128,
+    // The owner voiding the proposal, or the moderator taking it down
+    pub voided_by: &'a AccountId,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub(crate) struct ProposalCancelled<'a> {
+    pub proposal_id: U128,
+    pub cancelled_by: &'a AccountId,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct EventLog<'a> {
+    standard: &'static str,
+    version: &'static str,
+    #[serde(flatten)]
+    event: &'a Event<'a>,
+}
+
+impl Event<'_> {
+    pub(crate) fn emit(&self) {
+        let event_log = EventLog { standard: EVENT_STANDARD, version: EVENT_VERSION, event: self };
+        log!("EVENT_JSON:{}", serde_json::to_string(&event_log).unwrap());
+    }
+}
+
+// A newly recorded ballot and the one it replaced, if any, kept until the
+// ballot's storage is paid for so no event is logged for a ballot that fails
+pub(crate) struct RecordedBallot {
+    pub proposal_id: u128,
+    pub voter_id: AccountId,
+    pub earlier: Option<Ballot>,
+    pub vote: Vote,
+}
+
+impl RecordedBallot {
+    pub(crate) fn emit(&self) {
+        let proposal_id = U128(self.proposal_id);
+        match &self.earlier {
+            Some(old_ballot) => Event::VoteChanged(&[VoteChanged {
+                proposal_id,
+                voter_id: &self.voter_id,
+                old_ballot,
+                new_ballot: &self.vote.ballot,
+                weight: self.vote.weight,
+            }])
+            .emit(),
+            None => Event::VoteCast(&[VoteCast {
+                proposal_id,
+                voter_id: &self.voter_id,
+                ballot: &self.vote.ballot,
+                weight: self.vote.weight,
+            }])
+            .emit(),
+        }
+    }
+}
@@ -1,34 +1,637 @@
 // Find all NEAR documentation at https://docs.near.org
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::{log, env, near_bindgen, AccountId};
+use near_sdk::json_types::{Base58CryptoHash, Base64VecU8, U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::store::{LookupMap, UnorderedMap};
+use near_sdk::{
+    ext_contract, log, env, near_bindgen, AccountId, Balance, BorshStorageKey, CryptoHash, Gas, Promise, PromiseError,
+    PromiseOrValue,
+};
 use std::collections::BTreeMap;
 
+pub use crate::actions::{ActionStatus, ProposalAction};
+use crate::events::{Event, ProposalClosed, ProposalCreated, ProposalVoided, RecordedBallot};
+use crate::actions::validate_actions;
+pub use crate::membership::{Permission, Role};
+use crate::membership::default_public_permissions;
+use crate::migration::StateVersion;
+pub use crate::revisions::ProposalRevision;
+use crate::revisions::validate_text;
+pub use crate::runoff::RunoffRound;
+use crate::runoff::instant_runoff;
+pub use crate::storage::{StorageBalance, StorageBalanceBounds};
+use crate::storage::StorageAccount;
+pub use crate::treasury::{TreasuryBalance, TreasuryTransfer};
+use crate::treasury::AssetBalance;
+use crate::u256::U256;
 
+mod actions;
+mod cancellation;
+mod events;
+mod membership;
+mod migration;
+mod revisions;
+mod runoff;
+mod storage;
+mod treasury;
+mod upgrade;
+
+// Wide enough to multiply two u128 vote weights without overflowing
+mod u256 {
+    #![allow(clippy::assign_op_pattern, clippy::manual_div_ceil)]
+    uint::construct_uint! {
+        pub(crate) struct U256(4);
+    }
+}
+
+// Gas for looking up a voter's token balance and for recording the weighted vote
+const GAS_FOR_FT_BALANCE: Gas = Gas(10_000_000_000_000);
+const GAS_FOR_RECORD_WEIGHTED_VOTE: Gas = Gas(15_000_000_000_000);
+
+// Bond attached to a new proposal until the admin or governance changes it
+const DEFAULT_PROPOSAL_BOND: Balance = 1_000_000_000_000_000_000_000_000;
+
+// How many delegation hops are followed before a delegated vote is dropped
+const MAX_DELEGATION_DEPTH: usize = 8;
+
+// The part of the NEP-141 fungible token standard used to weigh ballots and pay out of the treasury
+#[ext_contract(ext_ft)]
+pub trait FungibleToken {
+    fn ft_balance_of(&self, account_id: AccountId) -> U128;
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+}
+
+// Storage prefixes for the persistent collections. Every proposal gets its
+// own prefix for its ballots, so a vote only loads that proposal's records.
+#[derive(BorshStorageKey, BorshSerialize)]
+enum StorageKey {
+    Proposals,
+    Votes,
+    Ballots { proposal_id: u128 },
+    Fate,
+    Rules,
+    Kinds,
+    Results,
+    Runoffs,
+    Stakes,
+    ProposalStakes,
+    AccountStakes,
+    CreditsSpent,
+    Commits,
+    ProposalCommits { proposal_id: u128 },
+    Delegations,
+    Members,
+    RolePermissions,
+    Bonds,
+    StorageAccounts,
+    ActionStatuses,
+    TreasuryTokens,
+    PendingTransfers,
+    StoredCode,
+    Statuses,
+    CancelApprovals,
+    Revisions,
+    ActionDeposits,
+    Delegators,
+}
+
+// Minimum participation a proposal needs before its result counts
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum Quorum {
+    // At least this many ballots must be cast
+    MinBallots(u32),
+}
+
+// The most options a multiple-choice proposal may offer
+const MAX_OPTIONS: usize = 10;
+
+// What voters are asked to choose between
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalKind {
+    #[default]
+    YesNo,
+    // Voters pick one of the listed options by index
+    MultipleChoice { options: Vec<String> },
+    // Voters rank the listed options; decided by instant runoff
+    RankedChoice { options: Vec<String> },
+    // A yes/no question whose function-call actions run once it passes
+    Actions { actions: Vec<ProposalAction> },
+    // A yes/no question paying out of the treasury once it passes
+    Transfer(TreasuryTransfer),
+    // A yes/no question upgrading the contract to the stored code once it passes
+    Upgrade { code_hash: Base58CryptoHash },
+    // A yes/no question replacing the text of an open proposal once it passes
+    Amendment {
+        proposal_id: u128,
+        proposal_text: String,
+        #[serde(default)]
+        metadata: ProposalMetadata,
+    },
+}
+
+impl ProposalKind {
+    // The options voters choose between, if not a yes/no proposal
+    fn options(&self) -> Option<&Vec<String>> {
+        match self {
+            ProposalKind::YesNo
+            | ProposalKind::Actions { .. }
+            | ProposalKind::Transfer(_)
+            | ProposalKind::Upgrade { .. }
+            | ProposalKind::Amendment { .. } => None,
+            ProposalKind::MultipleChoice { options } | ProposalKind::RankedChoice { options } => Some(options),
+        }
+    }
+
+    // Whether passing the proposal moves funds or changes code
+    fn executes(&self) -> bool {
+        matches!(self, ProposalKind::Actions { .. } | ProposalKind::Transfer(_) | ProposalKind::Upgrade { .. })
+    }
+
+    // Whether voters answer yes or no
+    fn is_yes_no(&self) -> bool {
+        self.options().is_none()
+    }
+
+    // Number of entries in the tally; yes/no proposals count [yes, no, abstain]
+    fn option_count(&self) -> usize {
+        self.options().map_or(3, Vec::len)
+    }
+}
+
+// An answer to a yes/no proposal. Abstaining counts towards the quorum but
+// not towards the majority. No and Yes keep the Borsh encoding of the bool
+// that stored ballots held before abstaining existed, so add choices last.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum VoteChoice {
+    No,
+    Yes,
+    Abstain,
+}
+
+impl From<bool> for VoteChoice {
+    fn from(yes: bool) -> Self {
+        if yes { VoteChoice::Yes } else { VoteChoice::No }
+    }
+}
+
+// The byte a secret ballot commits to
+impl From<VoteChoice> for u8 {
+    fn from(choice: VoteChoice) -> Self {
+        match choice {
+            VoteChoice::No => 0,
+            VoteChoice::Yes => 1,
+            VoteChoice::Abstain => 2,
+        }
+    }
+}
+
+// A single account's live ballot on a proposal
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(untagged)]
+pub enum Ballot {
+    YesNo(VoteChoice),
+    Choice(u32),
+    // Option indices from most to least preferred
+    Ranked(Vec<u32>),
+}
+
+impl Ballot {
+    // Index into the tally that this ballot counts towards
+    fn tally_index(&self) -> usize {
+        match *self {
+            Ballot::YesNo(VoteChoice::Yes) => 0,
+            Ballot::YesNo(VoteChoice::No) => 1,
+            Ballot::YesNo(VoteChoice::Abstain) => 2,
+            Ballot::Choice(option) => option as usize,
+            // a ranked ballot counts towards its first preference
+            Ballot::Ranked(ref ranking) => ranking[0] as usize,
+        }
+    }
+}
+
+// Optional details given when creating a proposal, beyond its title
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProposalMetadata {
+    #[serde(default)]
+    pub body: String,
+    pub link: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // sha256 of any off-chain content the proposal refers to
+    pub content_hash: Option<Base64VecU8>,
+}
+
+// A proposal in its current revision
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Proposal {
+    pub title: String,
+    pub body: String,
+    pub link: Option<String>,
+    pub tags: Vec<String>,
+    pub content_hash: Option<Base64VecU8>,
+    pub owner: AccountId,
+    pub created_at_block: U64,
+    // Nanoseconds since the Unix epoch
+    pub created_at: U64,
+    // Counts the edits and applied amendments, starting at 0
+    pub revision: u32,
+    pub revised_by: AccountId,
+    pub revised_at: U64,
+}
+
+// A ballot together with the voting power behind it
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Vote {
+    pub ballot: Ballot,
+    pub weight: U128,
+    // The revision of the proposal the ballot was cast on
+    pub revision: u32,
+}
+
+// Final count of a closed proposal
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProposalResult {
+    // Voting weight per option; [yes, no, abstain] for yes/no proposals
+    pub tally: Vec<U128>,
+    // Option picked by a multiple-choice proposal
+    pub winning_option: Option<u32>,
+    // Secret ballots committed but never revealed, and so not counted
+    #[serde(default)]
+    pub unrevealed_commits: u32,
+}
+
+// Share of yes votes a proposal needs to pass, out of all yes/no ballots
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum Threshold {
+    #[default]
+    SimpleMajority,
+    TwoThirds,
+    Unanimous,
+    Ratio { numerator: u32, denominator: u32 },
+}
+
+impl Threshold {
+    // The threshold as a numerator/denominator pair
+    fn as_ratio(&self) -> (u128, u128) {
+        match *self {
+            Threshold::SimpleMajority => (1, 2),
+            Threshold::TwoThirds => (2, 3),
+            Threshold::Unanimous => (1, 1),
+            Threshold::Ratio { numerator, denominator } => (numerator.into(), denominator.into()),
+        }
+    }
+}
+
+// What happens when yes and no votes are exactly even
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum TiePolicy {
+    #[default]
+    Pass,
+    Fail,
+    // Keep voting open for another voting period
+    Extend,
+}
+
+// How much each ballot counts for
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum VoteWeighting {
+    #[default]
+    OnePerAccount,
+    // Each ballot weighs the voter's balance in this NEP-141 token, read when
+    // the ballot is cast
+    TokenBalance { token_id: AccountId },
+    // Each ballot weighs the NEAR attached to it, locked until the proposal
+    // is closed or voided
+    Stake,
+    // Each voter gets this many voice credits per proposal; casting n votes
+    // costs n squared credits and counts n times
+    Quadratic { credits: u64 },
+}
+
+// How a closed proposal was decided
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalOutcome {
+    Passed,
+    Rejected,
+    FailedQuorum,
+}
+
+// Where a proposal is in its lifecycle
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalStatus {
+    // Created but not yet open to ballots; only its owner can publish it
+    Draft,
+    Open,
+    Passed,
+    Rejected,
+    // Taken down by its owner or a moderator
+    Voided,
+    // Closed without reaching its quorum
+    Expired,
+    // Passed, and its actions, transfer or upgrade have been carried out
+    Executed,
+    // Withdrawn by its owner
+    Cancelled,
+}
+
+impl ProposalStatus {
+    // Whether a proposal may move from this status to the given one
+    fn can_become(self, next: ProposalStatus) -> bool {
+        use ProposalStatus::*;
+        matches!(
+            (self, next),
+            (Draft, Open | Voided | Cancelled)
+                | (Open, Passed | Rejected | Voided | Expired | Cancelled)
+                | (Passed, Executed)
+        )
+    }
+
+    // Whether ballots can no longer change the proposal
+    fn is_closed(self) -> bool {
+        !matches!(self, ProposalStatus::Draft | ProposalStatus::Open)
+    }
+}
+
+// Where a proposal's bond stands
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum BondStatus {
+    // Held by the contract while the proposal is open
+    Held,
+    // Returned to the owner once the proposal closed
+    Refunded,
+    // Sent to the bond treasury, or added to the DAO treasury, after the proposal was voided
+    Slashed,
+}
+
+// Bond the owner attached when creating a proposal
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProposalBond {
+    pub amount: U128,
+    pub status: BondStatus,
+}
+
+// Commit and reveal deadlines of a secret-ballot proposal, in nanoseconds.
+// Voters first commit sha256(choice || salt), where choice is one byte,
+// 1 for yes, 0 for no and 2 to abstain, then reveal the choice and salt
+// once the commit window has closed.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SecretBallot {
+    pub commit_ends_at: U64,
+    pub reveal_ends_at: U64,
+}
+
+// Voting rules chosen when a proposal is created. Timestamps and durations
+// are in nanoseconds, matching env::block_timestamp().
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProposalRules {
+    // How long voting stays open, counted from creation
+    pub voting_period: Option<U64>,
+    // Absolute end of voting; derived from voting_period when only that is given
+    pub voting_ends_at: Option<U64>,
+    // Participation required for the vote to be decided
+    pub quorum: Option<Quorum>,
+    // Share of yes votes needed to pass
+    #[serde(default)]
+    pub threshold: Threshold,
+    // Outcome of an even yes/no split
+    #[serde(default)]
+    pub tie_policy: TiePolicy,
+    // How much each ballot counts for
+    #[serde(default)]
+    pub weighting: VoteWeighting,
+    // Commit and reveal windows, when ballots are kept secret until counted
+    pub secret_ballot: Option<SecretBallot>,
+}
+
+impl ProposalRules {
+    // Checks the rules against the proposal kind and fills in the derived
+    // deadline fields, panicking on any inconsistency
+    fn settle(&mut self, kind: &ProposalKind, now: u64) {
+        if let Some(secret) = &self.secret_ballot {
+            assert!(
+                self.voting_period.is_none() && self.voting_ends_at.is_none(),
+                "Secret Ballots End with their Reveal Window"
+            );
+            assert!(
+                now < secret.commit_ends_at.0 && secret.commit_ends_at.0 < secret.reveal_ends_at.0,
+                "Commit Window must End in the Future, before the Reveal Window"
+            );
+            assert!(
+                kind.is_yes_no() && self.weighting == VoteWeighting::OnePerAccount,
+                "Secret Ballots are only Available for Unweighted Yes/No Proposals"
+            );
+            assert!(self.tie_policy != TiePolicy::Extend, "Secret Ballots cannot Extend Voting on a Tie");
+            self.voting_ends_at = Some(secret.reveal_ends_at);
+        }
+        match (self.voting_period, self.voting_ends_at) {
+            (Some(_), Some(_)) => env::panic_str("Give either a Voting Period or an End Time, not both"),
+            (Some(period), None) => self.voting_ends_at = Some(U64(now + period.0)),
+            _ => {}
+        }
+        if let Some(ends_at) = self.voting_ends_at {
+            assert!(ends_at.0 > now, "Voting must End in the Future");
+            // keep the window length around so a tie can extend voting by it
+            self.voting_period = Some(U64(ends_at.0 - now));
+        }
+        if self.tie_policy == TiePolicy::Extend {
+            assert!(self.voting_period.is_some(), "Extending Voting on a Tie needs a Voting Period");
+        }
+        if let Threshold::Ratio { numerator, denominator } = self.threshold {
+            assert!(
+                numerator > 0 && numerator <= denominator,
+                "Threshold must be a Ratio between 0 and 1"
+            );
+        }
+        if let ProposalKind::Actions { actions } = kind {
+            validate_actions(actions);
+        }
+        if let Some(options) = kind.options() {
+            assert!(
+                (2..=MAX_OPTIONS).contains(&options.len()),
+                "A Proposal with Options needs 2 to {} of them", MAX_OPTIONS
+            );
+            assert!(
+                !matches!(self.weighting, VoteWeighting::Quadratic { .. }),
+                "Quadratic Voting is only Available for Yes/No Proposals"
+            );
+        }
+        if kind.executes() {
+            assert!(self.voting_ends_at.is_some(), "Proposals that Execute need a Voting Period");
+        }
+    }
+
+    // Raises the quorum to at least the given number of ballots
+    fn raise_quorum(&mut self, min_ballots: u32) {
+        let Quorum::MinBallots(current) = self.quorum.clone().unwrap_or(Quorum::MinBallots(0));
+        self.quorum = Some(Quorum::MinBallots(current.max(min_ballots)));
+    }
+
+    // Whether the voting window has passed at the given block timestamp
+    fn has_ended(&self, now: u64) -> bool {
+        self.voting_ends_at.is_some_and(|ends_at| now >= ends_at.0)
+    }
+
+    // Whether the given number of ballots satisfies the quorum
+    fn quorum_met(&self, ballot_count: u32) -> bool {
+        match self.quorum {
+            Some(Quorum::MinBallots(min_ballots)) => ballot_count >= min_ballots,
+            None => true,
+        }
+    }
+
+    // Decide a multiple-choice vote by plurality: Some(winner), Some(None)
+    // when a tie fails the proposal, or None when a tie extends voting
+    fn decide_winner(&self, tally: &[u128]) -> Option<Option<u32>> {
+        let top = tally.iter().copied().max().unwrap_or(0);
+        let mut leaders = (0..tally.len()).filter(|option| tally[*option] == top);
+        let first = leaders.next().map(|option| option as u32);
+        if leaders.next().is_none() {
+            return Some(first);
+        }
+        match self.tie_policy {
+            // the earliest listed of the tied options wins
+            TiePolicy::Pass => Some(first),
+            TiePolicy::Fail => Some(None),
+            TiePolicy::Extend => None,
+        }
+    }
+
+    // Decide a yes/no vote: Some(passed), or None when a tie extends voting.
+    // Nothing passes without a yes vote, whatever the tie policy.
+    fn decide(&self, upvotes: u128, downvotes: u128) -> Option<bool> {
+        if upvotes == 0 {
+            return Some(false);
+        }
+        if upvotes == downvotes {
+            return match self.tie_policy {
+                TiePolicy::Pass => Some(true),
+                TiePolicy::Fail => Some(false),
+                TiePolicy::Extend => None,
+            };
+        }
+        let (numerator, denominator) = self.threshold.as_ratio();
+        let ballots = U256::from(upvotes) + U256::from(downvotes);
+        Some(U256::from(upvotes) * U256::from(denominator) >= U256::from(numerator) * ballots)
+    }
+}
 
 // Define the contract structure
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize)]
 pub struct Contract {
+    // Must stay first, so migrate() can tell the layout of any stored state
+    version: StateVersion,
     proposal_count: u128,
     successful_proposal_count: u128,
     rejected_proposal_count: u128,
-    proposal_vals: BTreeMap<u128, String>,
-    proposal_owners: BTreeMap<u128, AccountId>,
-    proposal_votes: BTreeMap<u128, Vec<(AccountId, bool)>>,
-    proposal_fate: BTreeMap<u128, bool>,
+    failed_quorum_proposal_count: u128,
+    voided_proposal_count: u128,
+    cancelled_proposal_count: u128,
+    proposals: UnorderedMap<u128, Proposal>,
+    proposal_votes: LookupMap<u128, UnorderedMap<AccountId, Vote>>,
+    proposal_fate: LookupMap<u128, ProposalOutcome>,
+    proposal_statuses: LookupMap<u128, ProposalStatus>,
+    proposal_rules: LookupMap<u128, ProposalRules>,
+    proposal_kinds: LookupMap<u128, ProposalKind>,
+    proposal_results: LookupMap<u128, ProposalResult>,
+    proposal_runoffs: LookupMap<u128, Vec<RunoffRound>>,
+    stakes: LookupMap<(u128, AccountId), Balance>,
+    proposal_stakes: LookupMap<u128, Balance>,
+    account_stakes: LookupMap<AccountId, Balance>,
+    credits_spent: LookupMap<(u128, AccountId), u64>,
+    proposal_commits: LookupMap<u128, UnorderedMap<AccountId, Vec<u8>>>,
+    delegations: UnorderedMap<AccountId, AccountId>,
+    delegators: LookupMap<AccountId, Vec<AccountId>>,
+    admin: AccountId,
+    members: UnorderedMap<AccountId, Role>,
+    role_permissions: LookupMap<Role, Vec<Permission>>,
+    public_permissions: Vec<Permission>,
+    proposal_bond: Balance,
+    bond_treasury: Option<AccountId>,
+    proposal_bonds: LookupMap<u128, ProposalBond>,
+    execution_quorum: u32,
+    storage_accounts: LookupMap<AccountId, StorageAccount>,
+    action_statuses: LookupMap<u128, Vec<ActionStatus>>,
+    treasury_near: AssetBalance,
+    treasury_tokens: UnorderedMap<AccountId, AssetBalance>,
+    pending_transfers: UnorderedMap<u128, TreasuryTransfer>,
+    action_deposits: LookupMap<u128, Balance>,
+    stored_code: LookupMap<CryptoHash, Vec<u8>>,
+    cancel_approvals_needed: u32,
+    cancel_approvals: LookupMap<u128, Vec<AccountId>>,
+    proposal_revisions: LookupMap<u128, Vec<ProposalRevision>>,
 }
 
 // Define the default, which automatically initializes the contract
 impl Default for Contract{
     fn default() -> Self{
         Self{
+            version: StateVersion::default(),
             proposal_count: 0, 
             successful_proposal_count: 0,
             rejected_proposal_count: 0,
-            proposal_vals: BTreeMap::new(), 
-            proposal_owners: BTreeMap::new(), 
-            proposal_votes: BTreeMap::new(), 
-            proposal_fate: BTreeMap::new(),
+            failed_quorum_proposal_count: 0,
+            voided_proposal_count: 0,
+            cancelled_proposal_count: 0,
+            proposals: UnorderedMap::new(StorageKey::Proposals),
+            proposal_votes: LookupMap::new(StorageKey::Votes), 
+            proposal_fate: LookupMap::new(StorageKey::Fate),
+            proposal_statuses: LookupMap::new(StorageKey::Statuses),
+            proposal_rules: LookupMap::new(StorageKey::Rules),
+            proposal_kinds: LookupMap::new(StorageKey::Kinds),
+            proposal_results: LookupMap::new(StorageKey::Results),
+            proposal_runoffs: LookupMap::new(StorageKey::Runoffs),
+            stakes: LookupMap::new(StorageKey::Stakes),
+            proposal_stakes: LookupMap::new(StorageKey::ProposalStakes),
+            account_stakes: LookupMap::new(StorageKey::AccountStakes),
+            credits_spent: LookupMap::new(StorageKey::CreditsSpent),
+            proposal_commits: LookupMap::new(StorageKey::Commits),
+            delegations: UnorderedMap::new(StorageKey::Delegations),
+            delegators: LookupMap::new(StorageKey::Delegators),
+            admin: env::current_account_id(),
+            members: UnorderedMap::new(StorageKey::Members),
+            role_permissions: LookupMap::new(StorageKey::RolePermissions),
+            public_permissions: default_public_permissions(),
+            proposal_bond: DEFAULT_PROPOSAL_BOND,
+            bond_treasury: None,
+            proposal_bonds: LookupMap::new(StorageKey::Bonds),
+            execution_quorum: 1,
+            storage_accounts: LookupMap::new(StorageKey::StorageAccounts),
+            action_statuses: LookupMap::new(StorageKey::ActionStatuses),
+            treasury_near: AssetBalance::default(),
+            treasury_tokens: UnorderedMap::new(StorageKey::TreasuryTokens),
+            pending_transfers: UnorderedMap::new(StorageKey::PendingTransfers),
+            action_deposits: LookupMap::new(StorageKey::ActionDeposits),
+            stored_code: LookupMap::new(StorageKey::StoredCode),
+            cancel_approvals_needed: 1,
+            cancel_approvals: LookupMap::new(StorageKey::CancelApprovals),
+            proposal_revisions: LookupMap::new(StorageKey::Revisions),
         }
     }
 }
@@ -38,155 +641,881 @@ impl Default for Contract{
 impl Contract {
     // Public method - returns the current number of proposals
     pub fn get_proposal_count(&self) -> u128 {
-        return self.proposal_count.clone();
+        self.proposal_count
+    }
+
+    // Public method - returns the number of proposals that passed
+    pub fn get_successful_proposal_count(&self) -> u128 {
+        self.successful_proposal_count
+    }
+
+    // Public method - returns the number of proposals that were rejected
+    pub fn get_rejected_proposal_count(&self) -> u128 {
+        self.rejected_proposal_count
+    }
+
+    // Public method - returns the number of proposals that closed without quorum
+    pub fn get_failed_quorum_proposal_count(&self) -> u128 {
+        self.failed_quorum_proposal_count
+    }
+
+    // Public method - returns the number of proposals voided by their owner or a moderator
+    pub fn get_voided_proposal_count(&self) -> u128 {
+        self.voided_proposal_count
+    }
+
+    // Public method - returns the number of proposals withdrawn by their owner
+    pub fn get_cancelled_proposal_count(&self) -> u128 {
+        self.cancelled_proposal_count
+    }
+
+    // Public method - get a proposal with its metadata, owner and creation time
+    pub fn get_proposal(&self, proposal_id: u128) -> Option<Proposal> {
+        self.proposals.get(&proposal_id).cloned()
     }
 
-    // Public method - returns all proposals stored
+    // Public method - returns the titles of all proposals stored
     pub fn get_all_proposals(&self) -> BTreeMap<u128, String> {
-        return self.proposal_vals.clone();
+        self.proposals.iter().map(|(id, proposal)| (*id, proposal.title.clone())).collect()
+    }
+
+    // Public method - get all the votes for given proposal ID, one per account
+    pub fn get_all_votes(&self, proposal_id: u128) -> Vec<(AccountId, Ballot)> {
+        match self.proposal_votes.get(&proposal_id) {
+            Some(ballots) => ballots.iter().map(|(voter, vote)| (voter.clone(), vote.ballot.clone())).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    // Public method - get an account's ballot on a proposal along with its weight
+    pub fn get_vote(&self, proposal_id: u128, account_id: AccountId) -> Option<Vote> {
+        self.proposal_votes.get(&proposal_id)?.get(&account_id).cloned()
+    }
+
+    // Public method - get the current voting weight per option of a proposal;
+    // yes/no proposals count [yes, no, abstain]
+    pub fn get_tally(&self, proposal_id: u128) -> Vec<U128> {
+        assert!(self.proposals.get(&proposal_id).is_some(), "Proposal does not Exist");
+        self.tally(proposal_id).into_iter().map(U128).collect()
+    }
+
+    // Public method - get where a proposal is in its lifecycle
+    pub fn get_proposal_status(&self, proposal_id: u128) -> Option<ProposalStatus> {
+        self.proposal_statuses.get(&proposal_id).copied()
+    }
+
+    // Public method - get the IDs of all proposals with the given status
+    pub fn get_proposals_by_status(&self, status: ProposalStatus) -> Vec<u128> {
+        self.proposals
+            .keys()
+            .filter(|id| self.proposal_statuses.get(*id) == Some(&status))
+            .copied()
+            .collect()
+    }
+
+    // Public method - get how a proposal was decided, if it has closed
+    pub fn get_proposal_outcome(&self, proposal_id: u128) -> Option<ProposalOutcome> {
+        self.proposal_fate.get(&proposal_id).copied()
+    }
+
+    // Public method - get the final tally and winning option of a closed proposal
+    pub fn get_proposal_result(&self, proposal_id: u128) -> Option<ProposalResult> {
+        self.proposal_results.get(&proposal_id).cloned()
     }
 
-    // Public method - get all the votes for given proposal ID
-    pub fn get_all_votes(&self, proposal_id: u128) -> Vec<(AccountId, bool)> {
-       if  self.proposal_votes.get(&proposal_id).is_none() {
-        return Vec::new();
-       }
-        return self.proposal_votes.get(&proposal_id).clone().unwrap().to_vec();
+    // Public method - get the counting rounds of a closed ranked-choice proposal
+    pub fn get_runoff_rounds(&self, proposal_id: u128) -> Vec<RunoffRound> {
+        self.proposal_runoffs.get(&proposal_id).cloned().unwrap_or_default()
+    }
+
+    // Public method - get the NEAR an account has locked on a proposal
+    pub fn get_locked_stake(&self, proposal_id: u128, account_id: AccountId) -> U128 {
+        U128(self.stakes.get(&(proposal_id, account_id)).copied().unwrap_or(0))
+    }
+
+    // Public method - get the NEAR locked on a proposal by all of its voters
+    pub fn get_proposal_locked_stake(&self, proposal_id: u128) -> U128 {
+        U128(self.proposal_stakes.get(&proposal_id).copied().unwrap_or(0))
+    }
+
+    // Public method - get the NEAR an account has locked across all proposals
+    pub fn get_account_locked_stake(&self, account_id: AccountId) -> U128 {
+        U128(self.account_stakes.get(&account_id).copied().unwrap_or(0))
+    }
+
+    // Public method - get the voice credits an account has left on a quadratic proposal
+    pub fn get_voice_credits(&self, proposal_id: u128, account_id: AccountId) -> u64 {
+        match self.proposal_rules.get(&proposal_id).map(|rules| &rules.weighting) {
+            Some(VoteWeighting::Quadratic { credits }) => {
+                credits - self.credits_spent.get(&(proposal_id, account_id)).copied().unwrap_or(0)
+            }
+            _ => 0,
+        }
+    }
+
+    // Public method - get the accounts whose secret ballots are committed but not revealed
+    pub fn get_unrevealed_commits(&self, proposal_id: u128) -> Vec<AccountId> {
+        match self.proposal_commits.get(&proposal_id) {
+            Some(commits) => commits.keys().cloned().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    // Public method - get the account another account has delegated its vote to
+    pub fn get_delegate(&self, account_id: AccountId) -> Option<AccountId> {
+        self.delegations.get(&account_id).cloned()
+    }
+
+    // Public method - get the voting rules of a proposal
+    pub fn get_proposal_rules(&self, proposal_id: u128) -> Option<ProposalRules> {
+        self.proposal_rules.get(&proposal_id).cloned()
+    }
+
+    // Public method - get what kind of ballot a proposal asks for
+    pub fn get_proposal_kind(&self, proposal_id: u128) -> Option<ProposalKind> {
+        self.proposal_kinds.get(&proposal_id).cloned()
     }
     
-    // Public method - creates a new proposal
-    pub fn create_proposal(&mut self, proposal_text: String) {
-        let owner: AccountId = env::predecessor_account_id();
+    // Public method - get the bond needed to create a proposal
+    pub fn get_proposal_bond_amount(&self) -> U128 {
+        U128(self.proposal_bond)
+    }
+
+    // Public method - get the account slashed bonds are sent to, if any
+    pub fn get_bond_treasury(&self) -> Option<AccountId> {
+        self.bond_treasury.clone()
+    }
+
+    // Public method - get the bond of a proposal and whether it was refunded or slashed
+    pub fn get_proposal_bond(&self, proposal_id: u128) -> Option<ProposalBond> {
+        self.proposal_bonds.get(&proposal_id).cloned()
+    }
+
+    // Public method - get the fewest ballots a proposal that executes needs to be decided
+    pub fn get_execution_quorum(&self) -> u32 {
+        self.execution_quorum
+    }
+
+    // Public method - sets the bond needed to create a proposal
+    pub fn set_proposal_bond_amount(&mut self, amount: U128) {
+        self.assert_admin();
+        self.proposal_bond = amount.0;
+    }
+
+    // Public method - sets where slashed bonds go; without one they join the DAO treasury
+    pub fn set_bond_treasury(&mut self, account_id: Option<AccountId>) {
+        self.assert_admin();
+        self.bond_treasury = account_id;
+    }
+
+    // Public method - sets the fewest ballots a proposal that executes needs
+    // to be decided; proposals already created keep their quorum
+    pub fn set_execution_quorum(&mut self, min_ballots: u32) {
+        self.assert_admin();
+        assert!(min_ballots > 0, "Proposals that Execute need at least One Ballot");
+        self.execution_quorum = min_ballots;
+    }
+
+    // Public method - creates a new proposal, optionally with a voting window
+    // given either as a duration or as an absolute end time, and optionally
+    // with a list of options to choose from instead of yes/no. The proposal
+    // text is its title; a body, link, tags and content hash may be given as
+    // metadata. The proposal bond must be attached; anything above it is
+    // refunded. The bytes the proposal takes up are charged to the owner's
+    // storage balance. A draft stays closed to ballots until its owner
+    // publishes it, and its voting window starts then.
+    #[payable]
+    pub fn create_proposal(
+        &mut self,
+        proposal_text: String,
+        rules: Option<ProposalRules>,
+        kind: Option<ProposalKind>,
+        metadata: Option<ProposalMetadata>,
+        draft: Option<bool>,
+    ) {
+        let owner: AccountId = self.assert_permitted(Permission::Propose);
+        let usage_before = env::storage_usage();
+        let mut rules = rules.unwrap_or_default();
+        let kind = kind.unwrap_or_default();
+        let draft = draft.unwrap_or(false);
+        if draft {
+            // checked now, settled when published
+            rules.clone().settle(&kind, env::block_timestamp());
+        } else {
+            rules.settle(&kind, env::block_timestamp());
+        }
+        if kind.executes() {
+            // the proposer may ask for more ballots, never for fewer
+            rules.raise_quorum(self.execution_quorum);
+        }
+        let metadata = metadata.unwrap_or_default();
+        validate_text(&proposal_text, &metadata);
+        match &kind {
+            ProposalKind::Actions { actions } => self.assert_actions_proposable(&owner, actions),
+            ProposalKind::Upgrade { code_hash } => self.assert_upgrade_proposable(&owner, code_hash),
+            ProposalKind::Amendment { proposal_id, proposal_text, metadata } => {
+                self.assert_amendable(*proposal_id);
+                validate_text(proposal_text, metadata);
+            }
+            _ => {}
+        }
+        let deposit = env::attached_deposit();
+        assert!(
+            deposit >= self.proposal_bond,
+            "Attach a Bond of {} yoctoNEAR to Create a Proposal", self.proposal_bond
+        );
+        if deposit > self.proposal_bond {
+            Promise::new(owner.clone()).transfer(deposit - self.proposal_bond);
+        }
         
-        log!("Registering New Proposal: {}", proposal_text);
-        let new_prop_count: u128 = self.proposal_count.clone() + 1;
+        let new_prop_count: u128 = self.proposal_count + 1;
         self.proposal_count = new_prop_count;
-        self.proposal_vals.insert(new_prop_count, proposal_text);
-        self.proposal_owners.insert(new_prop_count, owner);
-        self.proposal_votes.insert(new_prop_count, Vec::new());
+        match &kind {
+            ProposalKind::Transfer(transfer) => self.commit_transfer(new_prop_count, transfer),
+            ProposalKind::Actions { actions } => self.commit_action_deposits(new_prop_count, actions),
+            _ => {}
+        }
+        let proposal = Proposal {
+            title: proposal_text,
+            body: metadata.body,
+            link: metadata.link,
+            tags: metadata.tags,
+            content_hash: metadata.content_hash,
+            owner: owner.clone(),
+            created_at_block: U64(env::block_height()),
+            created_at: U64(env::block_timestamp()),
+            revision: 0,
+            revised_by: owner.clone(),
+            revised_at: U64(env::block_timestamp()),
+        };
+        self.proposals.insert(new_prop_count, proposal);
+        self.proposal_votes.insert(
+            new_prop_count,
+            UnorderedMap::new(StorageKey::Ballots { proposal_id: new_prop_count }),
+        );
+        if rules.secret_ballot.is_some() {
+            self.proposal_commits.insert(
+                new_prop_count,
+                UnorderedMap::new(StorageKey::ProposalCommits { proposal_id: new_prop_count }),
+            );
+        }
+        self.proposal_rules.insert(new_prop_count, rules);
+        self.proposal_kinds.insert(new_prop_count, kind);
+        let status = if draft { ProposalStatus::Draft } else { ProposalStatus::Open };
+        self.proposal_statuses.insert(new_prop_count, status);
+        let bond = ProposalBond { amount: U128(self.proposal_bond), status: BondStatus::Held };
+        self.proposal_bonds.insert(new_prop_count, bond);
+        self.charge_storage(&owner, new_prop_count, usage_before);
+        // emitted last, so no event is logged for a proposal that fails to be created
+        Event::ProposalCreated(&[ProposalCreated {
+            proposal_id: U128(new_prop_count),
+            owner_id: &owner,
+            title: &self.proposals[&new_prop_count].title,
+            kind: &self.proposal_kinds[&new_prop_count],
+        }])
+        .emit();
     }
 
-    // Public method - allows voting on a proposal (currently voting isn't capped to 1)
-    pub fn vote_on_proposal(&mut self, proposal_id: u128, vote_choice: bool) {
-        let proposal_exists = self.proposal_vals.get(&proposal_id);
-        assert!(!proposal_exists.is_none(), "Proposal does not Exist");
-        let proposal_status = self.proposal_fate.get(&proposal_id);
-        assert!(proposal_status.is_none(), "Proposal has Already Closed!");
-        let voter: AccountId = env::predecessor_account_id();
-        let mut votes_vec = self.proposal_votes.get(&proposal_id).unwrap().clone();
-        votes_vec.push((voter.clone(), vote_choice.clone()));
-        self.proposal_votes.remove(&proposal_id);
-        self.proposal_votes.insert(proposal_id, (votes_vec).clone().to_vec());
+    // Public method - opens a draft proposal to ballots, starting its voting window
+    pub fn publish_proposal(&mut self, proposal_id: u128) {
+        let status = self.status_of(proposal_id);
+        assert!(status == ProposalStatus::Draft, "Proposal is not a Draft");
+        let caller: AccountId = self.assert_permitted(Permission::Propose);
+        assert_eq!(&caller, &self.proposals[&proposal_id].owner);
+        let kind = self.proposal_kinds[&proposal_id].clone();
+        self.proposal_rules.get_mut(&proposal_id).unwrap().settle(&kind, env::block_timestamp());
+        log!("Publishing Proposal {}", proposal_id);
+        self.set_status(proposal_id, ProposalStatus::Open);
+    }
 
+    // Public method - allows voting yes, no or abstain on a yes/no proposal, one
+    // live ballot per account. Voting again replaces the account's earlier choice. On stake-weighted
+    // proposals the attached NEAR is added to the voter's locked stake;
+    // otherwise any attached deposit is refunded. On quadratic proposals the
+    // magnitude (default 1) is the number of votes cast, paid for in credits.
+    #[payable]
+    pub fn vote_on_proposal(
+        &mut self,
+        proposal_id: u128,
+        vote_choice: VoteChoice,
+        magnitude: Option<u32>,
+    ) -> PromiseOrValue<()> {
+        self.assert_voting_open(proposal_id);
+        assert!(
+            self.proposal_kinds[&proposal_id].is_yes_no(),
+            "Proposal is not a Yes/No Proposal"
+        );
+        let voter: AccountId = self.assert_permitted(Permission::Vote);
+        self.cast_ballot(proposal_id, voter, Ballot::YesNo(vote_choice), magnitude)
     }
 
-    // Public method - allow the proposal creator to close the proposal
-    pub fn close_proposal(&mut self, proposal_id: u128) -> bool{
-        let proposal_exists = self.proposal_vals.get(&proposal_id);
-        assert!(!proposal_exists.is_none(), "Proposal does not Exist");
-        let proposal_status = self.proposal_fate.get(&proposal_id);
-        assert!(proposal_status.is_none(), "Proposal has Already Closed!");
-        let caller: AccountId = env::predecessor_account_id();
-        assert_eq!(&caller, self.proposal_owners.get(&proposal_id.clone()).unwrap());
-        log!("Closing Proposal: {}", proposal_id);
-        let votes_vec = self.proposal_votes.get(&proposal_id).unwrap().clone();
-        let mut upvotes : u128 = 0;
-        for item in votes_vec.clone() {
-            if item.1 {
-                upvotes += 1;
+    // Public method - allows voting for one option of a multiple-choice proposal.
+    // Voting again replaces the account's earlier choice.
+    #[payable]
+    pub fn vote_for_option(&mut self, proposal_id: u128, option_index: u32) -> PromiseOrValue<()> {
+        self.assert_voting_open(proposal_id);
+        match &self.proposal_kinds[&proposal_id] {
+            ProposalKind::MultipleChoice { options } => {
+                assert!((option_index as usize) < options.len(), "Option does not Exist");
+            }
+            _ => env::panic_str("Proposal is not a Multiple Choice Proposal"),
+        }
+        let voter: AccountId = self.assert_permitted(Permission::Vote);
+        self.cast_ballot(proposal_id, voter, Ballot::Choice(option_index), None)
+    }
+
+    // Public method - allows submitting a ranking of the options of a
+    // ranked-choice proposal, most preferred first. Options left out are
+    // ranked below all listed ones. Voting again replaces the earlier ranking.
+    #[payable]
+    pub fn vote_ranked(&mut self, proposal_id: u128, ranking: Vec<u32>) -> PromiseOrValue<()> {
+        self.assert_voting_open(proposal_id);
+        match &self.proposal_kinds[&proposal_id] {
+            ProposalKind::RankedChoice { options } => {
+                assert!(!ranking.is_empty(), "Ranking is Empty");
+                let mut seen = vec![false; options.len()];
+                for option in &ranking {
+                    assert!((*option as usize) < options.len(), "Option does not Exist");
+                    assert!(!seen[*option as usize], "Option Ranked more than Once");
+                    seen[*option as usize] = true;
+                }
             }
+            _ => env::panic_str("Proposal is not a Ranked Choice Proposal"),
+        }
+        let voter: AccountId = self.assert_permitted(Permission::Vote);
+        self.cast_ballot(proposal_id, voter, Ballot::Ranked(ranking), None)
+    }
+
+    // Public method - commits a secret ballot as sha256(choice || salt) while
+    // the commit window is open. Committing again replaces the commitment.
+    pub fn commit_vote(&mut self, proposal_id: u128, commitment: Base64VecU8) {
+        self.assert_voting_open(proposal_id);
+        let secret = self.proposal_rules[&proposal_id].secret_ballot.clone();
+        let secret = secret.unwrap_or_else(|| env::panic_str("Proposal does not use Secret Ballots"));
+        assert!(env::block_timestamp() < secret.commit_ends_at.0, "Commit Window has Ended!");
+        assert_eq!(commitment.0.len(), 32, "Commitment must be a SHA-256 Hash");
+        let voter: AccountId = self.assert_permitted(Permission::Vote);
+        let usage_before = env::storage_usage();
+        self.proposal_commits.get_mut(&proposal_id).unwrap().insert(voter.clone(), commitment.0);
+        self.charge_storage(&voter, proposal_id, usage_before);
+    }
+
+    // Public method - reveals a committed ballot once the commit window has
+    // closed. Only ballots matching their commitment are counted.
+    pub fn reveal_vote(&mut self, proposal_id: u128, vote_choice: VoteChoice, salt: String) {
+        self.assert_voting_open(proposal_id);
+        let secret = self.proposal_rules[&proposal_id].secret_ballot.clone();
+        let secret = secret.unwrap_or_else(|| env::panic_str("Proposal does not use Secret Ballots"));
+        assert!(env::block_timestamp() >= secret.commit_ends_at.0, "Commit Window is Still Open");
+        let voter: AccountId = self.assert_permitted(Permission::Vote);
+        let usage_before = env::storage_usage();
+        let commits = self.proposal_commits.get_mut(&proposal_id).unwrap();
+        let commitment = commits.get(&voter).unwrap_or_else(|| env::panic_str("No Committed Ballot to Reveal"));
+        let revealed = env::sha256(&[&[u8::from(vote_choice)], salt.as_bytes()].concat());
+        assert!(*commitment == revealed, "Ballot does not Match its Commitment");
+        commits.remove(&voter);
+        let recorded = self.record_ballot(proposal_id, voter.clone(), Ballot::YesNo(vote_choice), 1);
+        self.charge_storage(&voter, proposal_id, usage_before);
+        if let Some(recorded) = recorded {
+            recorded.emit();
+        }
+    }
+
+    // Public method - hands the caller's vote, and any votes delegated to the
+    // caller, to another account. On every one-vote-per-account proposal the
+    // caller does not vote on directly, the caller's vote follows the ballot
+    // of its delegate when counted. The bytes the delegation takes up are
+    // charged to the caller's storage balance.
+    pub fn delegate(&mut self, to: AccountId) {
+        let delegator: AccountId = self.assert_permitted(Permission::Vote);
+        assert!(delegator != to, "Cannot Delegate to Yourself");
+        let usage_before = env::storage_usage();
+        self.remove_delegation(&delegator);
+        assert!(!self.delegate_chain(&to).contains(&delegator), "Delegation would Form a Cycle");
+        log!("{} Delegates their Vote to {}", delegator, to);
+        self.add_delegation(delegator.clone(), to);
+        self.charge_storage(&delegator, 0, usage_before);
+    }
+
+    // Public method - takes back the caller's delegated vote
+    pub fn undelegate(&mut self) {
+        let delegator: AccountId = env::predecessor_account_id();
+        let usage_before = env::storage_usage();
+        assert!(self.remove_delegation(&delegator), "No Delegation to Remove");
+        log!("{} Stops Delegating their Vote", delegator);
+        self.charge_storage(&delegator, 0, usage_before);
+    }
+
+    // Callback - records a token-weighted ballot once the voter's balance is known
+    #[private]
+    pub fn on_vote_weight(
+        &mut self,
+        proposal_id: u128,
+        voter: AccountId,
+        ballot: Ballot,
+        #[callback_result] balance: Result<U128, PromiseError>,
+    ) {
+        let balance = balance.unwrap_or_else(|_| env::panic_str("Could not Read Token Balance"));
+        assert!(balance.0 > 0, "Voter holds no Voting Tokens");
+        // the proposal may have closed while the balance was being fetched
+        self.assert_voting_open(proposal_id);
+        let usage_before = env::storage_usage();
+        let recorded = self.record_ballot(proposal_id, voter.clone(), ballot, balance.0);
+        self.charge_storage(&voter, proposal_id, usage_before);
+        if let Some(recorded) = recorded {
+            recorded.emit();
+        }
+    }
+
+    // Public method - returns the caller's stake on a proposal once it is
+    // closed or voided
+    pub fn claim_stake(&mut self, proposal_id: u128) -> Promise {
+        let closed = self.proposal_statuses.get(&proposal_id).is_some_and(|status| status.is_closed());
+        assert!(closed, "Proposal is Still Open");
+        let account_id: AccountId = env::predecessor_account_id();
+        let stake = self.stakes.remove(&(proposal_id, account_id.clone())).unwrap_or(0);
+        assert!(stake > 0, "No Stake to Claim");
+        *self.proposal_stakes.get_mut(&proposal_id).unwrap() -= stake;
+        let account_total = self.account_stakes.get_mut(&account_id).unwrap();
+        *account_total -= stake;
+        if *account_total == 0 {
+            self.account_stakes.remove(&account_id);
         }
-        if 2 * upvotes >= votes_vec.clone().len().try_into().unwrap(){
-            self.proposal_fate.insert(proposal_id, true);
+        log!("Returning Stake of {} to {} on Proposal {}", stake, account_id, proposal_id);
+        Promise::new(account_id).transfer(stake)
+    }
+
+    // Public method - allow the proposal creator to close the proposal, unless
+    // it executes once passed. Once the voting window has passed, anyone
+    // permitted to close may finalize it.
+    pub fn close_proposal(&mut self, proposal_id: u128) -> bool{
+        self.assert_open(proposal_id);
+        let caller: AccountId = self.assert_permitted(Permission::Close);
+        let voting_ended = self.proposal_rules[&proposal_id].has_ended(env::block_timestamp());
+        if !voting_ended {
+            assert!(
+                !self.proposal_kinds[&proposal_id].executes(),
+                "Proposals that Execute cannot be Closed before Voting Ends"
+            );
+            assert_eq!(&caller, &self.proposals[&proposal_id].owner);
+        }
+        // secret ballots are only counted once everyone had the chance to reveal
+        let secret = self.proposal_rules[&proposal_id].secret_ballot.is_some();
+        assert!(!secret || voting_ended, "Reveal Window is Still Open");
+        let votes = self.counted_votes(proposal_id);
+        let mut tally = vec![0; self.proposal_kinds[&proposal_id].option_count()];
+        for vote in &votes {
+            tally[vote.ballot.tally_index()] += vote.weight.0;
+        }
+        let rules = &self.proposal_rules[&proposal_id];
+        let unrevealed_commits = self.proposal_commits.get(&proposal_id).map_or(0, |commits| commits.len());
+        if !rules.quorum_met(votes.len() as u32) {
+            self.proposal_fate.insert(proposal_id, ProposalOutcome::FailedQuorum);
+            self.set_status(proposal_id, ProposalStatus::Expired);
+            let tally: Vec<U128> = tally.into_iter().map(U128).collect();
+            self.proposal_results.insert(
+                proposal_id,
+                ProposalResult { tally: tally.clone(), winning_option: None, unrevealed_commits },
+            );
+            self.failed_quorum_proposal_count += 1;
+            self.settle_bond(proposal_id, BondStatus::Refunded);
+            self.release_commitments(proposal_id);
+            Event::ProposalClosed(&[ProposalClosed {
+                proposal_id: U128(proposal_id),
+                outcome: ProposalOutcome::FailedQuorum,
+                tally: &tally,
+                winning_option: None,
+            }])
+            .emit();
+            return false;
+        }
+        let decision = match &self.proposal_kinds[&proposal_id] {
+            ProposalKind::YesNo
+            | ProposalKind::Actions { .. }
+            | ProposalKind::Transfer(_)
+            | ProposalKind::Upgrade { .. }
+            | ProposalKind::Amendment { .. } => {
+                rules.decide(tally[0], tally[1]).map(|passed| (passed, None))
+            }
+            ProposalKind::MultipleChoice { .. } => rules
+                .decide_winner(&tally)
+                .map(|winner| (winner.is_some(), winner)),
+            ProposalKind::RankedChoice { options } => {
+                let rankings: Vec<(&Vec<u32>, u128)> = votes
+                    .iter()
+                    .filter_map(|vote| match &vote.ballot {
+                        Ballot::Ranked(ranking) => Some((ranking, vote.weight.0)),
+                        _ => None,
+                    })
+                    .collect();
+                let rounds = instant_runoff(options.len(), &rankings);
+                // the last round holds a majority winner or an unbreakable tie
+                let final_totals: Vec<u128> = rounds.last().unwrap().totals.iter().map(|total| total.0).collect();
+                let decision = rules
+                    .decide_winner(&final_totals)
+                    .map(|winner| (winner.is_some(), winner));
+                if decision.is_some() {
+                    self.proposal_runoffs.insert(proposal_id, rounds);
+                }
+                decision
+            }
+        };
+        let Some((passed, winning_option)) = decision else {
+            let rules = self.proposal_rules.get_mut(&proposal_id).unwrap();
+            // settle() only allows extending proposals that have a voting period
+            let period = rules.voting_period.unwrap();
+            rules.voting_ends_at = Some(U64(env::block_timestamp() + period.0));
+            log!("Proposal {} is Tied, Voting Extended", proposal_id);
+            return false;
+        };
+        let tally: Vec<U128> = tally.into_iter().map(U128).collect();
+        let outcome = if passed { ProposalOutcome::Passed } else { ProposalOutcome::Rejected };
+        self.proposal_results.insert(
+            proposal_id,
+            ProposalResult { tally: tally.clone(), winning_option, unrevealed_commits },
+        );
+        self.settle_bond(proposal_id, BondStatus::Refunded);
+        if passed {
+            self.proposal_fate.insert(proposal_id, ProposalOutcome::Passed);
+            self.set_status(proposal_id, ProposalStatus::Passed);
             self.successful_proposal_count += 1;
-            return true;
         }
         else {
-            self.proposal_fate.insert(proposal_id, false);
+            self.proposal_fate.insert(proposal_id, ProposalOutcome::Rejected);
+            self.set_status(proposal_id, ProposalStatus::Rejected);
             self.rejected_proposal_count += 1;
-            return false;
+            self.release_commitments(proposal_id);
         }
-        
-        
+        Event::ProposalClosed(&[ProposalClosed {
+            proposal_id: U128(proposal_id),
+            outcome,
+            tally: &tally,
+            winning_option,
+        }])
+        .emit();
+        if passed {
+            if let ProposalKind::Actions { actions } = self.proposal_kinds[&proposal_id].clone() {
+                self.execute_actions(proposal_id, &actions);
+            }
+            if let ProposalKind::Upgrade { code_hash } = self.proposal_kinds[&proposal_id].clone() {
+                self.execute_upgrade(proposal_id, &code_hash);
+            }
+            self.apply_amendment(proposal_id);
+            self.execute_transfer(proposal_id);
+        }
+        passed
     }
 
-    // Public method - allow the proposal creator to void the proposal if too few votes
+    // Public method - allow the proposal creator to void the proposal if too few votes,
+    // i.e. no yes votes, or no ballots at all on a proposal with options
     pub fn void_proposal(&mut self, proposal_id: u128) -> bool{
-        let proposal_exists = self.proposal_vals.get(&proposal_id);
-        assert!(!proposal_exists.is_none(), "Proposal does not Exist");
-        let proposal_status = self.proposal_fate.get(&proposal_id);
-        assert!(proposal_status.is_none(), "Proposal has Already Closed!");
-        let caller: AccountId = env::predecessor_account_id();
-        assert_eq!(&caller, self.proposal_owners.get(&proposal_id.clone()).unwrap());
-        log!("Voiding Proposal: {}", proposal_id);
-        let votes_vec = self.proposal_votes.get(&proposal_id).unwrap().clone();
-        let mut upvotes : u128 = 0;
-        for item in votes_vec.clone() {
-            if item.1 {
-                upvotes += 1;
-            }
-        }
-        if upvotes == 0{
-            self.proposal_fate.insert(proposal_id, false);
-            self.rejected_proposal_count += 1;
-            return true;
+        self.assert_open(proposal_id);
+        let caller: AccountId = self.assert_permitted(Permission::Void);
+        assert_eq!(&caller, &self.proposals[&proposal_id].owner);
+        let support = if self.proposal_kinds[&proposal_id].is_yes_no() {
+            self.tally(proposal_id)[0]
+        } else {
+            self.proposal_votes[&proposal_id].len().into()
+        };
+        if support == 0{
+            self.set_status(proposal_id, ProposalStatus::Voided);
+            self.voided_proposal_count += 1;
+            self.settle_bond(proposal_id, BondStatus::Slashed);
+            self.release_commitments(proposal_id);
+            Event::ProposalVoided(&[ProposalVoided { proposal_id: U128(proposal_id), voided_by: &caller }]).emit();
+            true
         }
         else {
-            
-            
-            return false;
+            false
         }
-        
-        
+    }
+
+    // Public method - lets a moderator take down an open or draft proposal
+    // whatever its ballots, voiding it and slashing its bond
+    pub fn moderate_proposal(&mut self, proposal_id: u128) {
+        assert!(!self.status_of(proposal_id).is_closed(), "Proposal has Already Closed!");
+        let moderator: AccountId = self.assert_permitted(Permission::Moderate);
+        self.set_status(proposal_id, ProposalStatus::Voided);
+        self.voided_proposal_count += 1;
+        self.settle_bond(proposal_id, BondStatus::Slashed);
+        self.release_commitments(proposal_id);
+        Event::ProposalVoided(&[ProposalVoided { proposal_id: U128(proposal_id), voided_by: &moderator }]).emit();
     }
 }
 
-/*
- * The rest of this file holds the inline tests for the code above
- * Learn more about Rust tests: https://doc.rust-lang.org/book/ch11-01-writing-tests.html
- */
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use near_sdk::testing_env;
-    use near_sdk::test_utils::VMContextBuilder;
-    use near_sdk::Balance;
+// Internal helpers, not exposed as contract methods
+impl Contract {
+    // The status of a proposal, panicking if it does not exist
+    fn status_of(&self, proposal_id: u128) -> ProposalStatus {
+        let status = self.proposal_statuses.get(&proposal_id);
+        *status.unwrap_or_else(|| env::panic_str("Proposal does not Exist"))
+    }
 
-    //const BENEFICIARY: &str = "beneficiary";
-    const NEAR: u128 = 1000000000000000000000000;
+    // Panics unless the proposal exists and is open
+    fn assert_open(&self, proposal_id: u128) {
+        let status = self.status_of(proposal_id);
+        assert!(status != ProposalStatus::Draft, "Proposal is a Draft; Publish it First");
+        assert!(!status.is_closed(), "Proposal has Already Closed!");
+    }
 
-    #[test]
-    fn test_get_default_proposals() {
-        let contract = Contract::default();
-        // to check if the initialization is with no proposals already stored
-        assert_eq!(
-            contract.get_proposal_count(),
-            0
-        );
+    // Whether any ballot, revealed or not, was cast on the proposal
+    pub(crate) fn has_ballots(&self, proposal_id: u128) -> bool {
+        !self.proposal_votes[&proposal_id].is_empty()
+            || self.proposal_commits.get(&proposal_id).is_some_and(|commits| !commits.is_empty())
     }
 
-    #[test]
-    fn test_create_new_proposal() {
-        let mut contract = Contract::default();
-        let acc: AccountId = "harry.near".parse().unwrap();
-        set_context(acc, 10*NEAR);
-        contract.create_proposal("Should bears be legal pets?".to_string());
-        assert_eq!(
-            contract.get_proposal_count(),
-            1
-        );
+    // Moves a proposal to the given status, panicking on an illegal transition
+    pub(crate) fn set_status(&mut self, proposal_id: u128, next: ProposalStatus) {
+        let status = self.proposal_statuses.get_mut(&proposal_id).unwrap();
+        assert!(status.can_become(next), "Proposal cannot go from {:?} to {:?}", status, next);
+        *status = next;
     }
 
-    #[test]
-    fn test_vote_on_proposal() {
-        let mut contract = Contract::default();
-        let acc1: AccountId = "harry.near".parse().unwrap();
-        set_context(acc1, 10*NEAR);
-        contract.create_proposal("Should bears be legal pets?".to_string());
-        let acc2: AccountId = "mikky.near".parse().unwrap();
-        set_context(acc2, 10*NEAR);
-        contract.vote_on_proposal(1, true);
-        assert_eq!(
+    // Panics unless the proposal exists and is still accepting ballots
+    fn assert_voting_open(&self, proposal_id: u128) {
+        self.assert_open(proposal_id);
+        let voting_ended = self.proposal_rules[&proposal_id].has_ended(env::block_timestamp());
+        assert!(!voting_ended, "Voting Period has Ended!");
+    }
+
+    // Records the ballot with the weight given by the proposal's weighting,
+    // first looking up the voter's token balance when it is token-weighted
+    fn cast_ballot(
+        &mut self,
+        proposal_id: u128,
+        voter: AccountId,
+        ballot: Ballot,
+        magnitude: Option<u32>,
+    ) -> PromiseOrValue<()> {
+        assert!(
+            self.proposal_rules[&proposal_id].secret_ballot.is_none(),
+            "Proposal uses Secret Ballots; Commit your Vote Instead"
+        );
+        let deposit = env::attached_deposit();
+        let weighting = &self.proposal_rules[&proposal_id].weighting;
+        if deposit > 0 && *weighting != VoteWeighting::Stake {
+            Promise::new(voter.clone()).transfer(deposit);
+        }
+        if !matches!(weighting, VoteWeighting::Quadratic { .. }) {
+            assert!(magnitude.is_none(), "Vote Magnitude only applies to Quadratic Proposals");
+        }
+        self.assert_storage_registered(&voter);
+        let usage_before = env::storage_usage();
+        let recorded = match weighting {
+            VoteWeighting::OnePerAccount => self.record_ballot(proposal_id, voter.clone(), ballot, 1),
+            VoteWeighting::Stake => {
+                let stake = self.lock_stake(proposal_id, &voter, deposit);
+                self.record_ballot(proposal_id, voter.clone(), ballot, stake)
+            }
+            VoteWeighting::Quadratic { credits } => {
+                let votes = magnitude.unwrap_or(1);
+                assert!(votes > 0, "Cast at least One Vote");
+                self.spend_credits(proposal_id, &voter, votes, *credits);
+                self.record_ballot(proposal_id, voter.clone(), ballot, votes.into())
+            }
+            // the ballot is recorded, and its storage charged, once the balance is known
+            VoteWeighting::TokenBalance { token_id } => {
+                return ext_ft::ext(token_id.clone())
+                    .with_static_gas(GAS_FOR_FT_BALANCE)
+                    .ft_balance_of(voter.clone())
+                    .then(
+                        Self::ext(env::current_account_id())
+                            .with_static_gas(GAS_FOR_RECORD_WEIGHTED_VOTE)
+                            .on_vote_weight(proposal_id, voter, ballot),
+                    )
+                    .into()
+            }
+        };
+        self.charge_storage(&voter, proposal_id, usage_before);
+        if let Some(recorded) = recorded {
+            recorded.emit();
+        }
+        PromiseOrValue::Value(())
+    }
+
+    // Adds the deposit to the voter's stake on a proposal, returning the new total
+    fn lock_stake(&mut self, proposal_id: u128, voter: &AccountId, deposit: Balance) -> Balance {
+        let key = (proposal_id, voter.clone());
+        let stake = self.stakes.get(&key).copied().unwrap_or(0) + deposit;
+        assert!(stake > 0, "Attach a Deposit to Stake on this Proposal");
+        if deposit > 0 {
+            self.stakes.insert(key, stake);
+            *self.proposal_stakes.entry(proposal_id).or_insert(0) += deposit;
+            *self.account_stakes.entry(voter.clone()).or_insert(0) += deposit;
+        }
+        stake
+    }
+
+    // Charges n squared voice credits for n votes, refunding whatever the
+    // voter's earlier ballot on the proposal cost
+    fn spend_credits(&mut self, proposal_id: u128, voter: &AccountId, votes: u32, credits: u64) {
+        let cost = u64::from(votes) * u64::from(votes);
+        assert!(cost <= credits, "Not Enough Voice Credits: {} Votes cost {} of {}", votes, cost, credits);
+        self.credits_spent.insert((proposal_id, voter.clone()), cost);
+    }
+
+    // Stores the voter's ballot, replacing any earlier one. Returns the ballot
+    // to log once its storage is charged, unless it repeats the earlier one.
+    fn record_ballot(
+        &mut self,
+        proposal_id: u128,
+        voter: AccountId,
+        ballot: Ballot,
+        weight: u128,
+    ) -> Option<RecordedBallot> {
+        let revision = self.proposals[&proposal_id].revision;
+        let ballots = self.proposal_votes.get_mut(&proposal_id).unwrap();
+        let vote = Vote { ballot, weight: U128(weight), revision };
+        let earlier = ballots.insert(voter.clone(), vote.clone());
+        if earlier.as_ref() == Some(&vote) {
+            return None;
+        }
+        Some(RecordedBallot { proposal_id, voter_id: voter, earlier: earlier.map(|earlier| earlier.ballot), vote })
+    }
+
+    // Refunds the bond to the proposal owner, or slashes it by sending it to
+    // the bond treasury, or adding it to the DAO treasury when there is none
+    fn settle_bond(&mut self, proposal_id: u128, status: BondStatus) {
+        let Some(bond) = self.proposal_bonds.get_mut(&proposal_id) else { return };
+        bond.status = status;
+        let amount = bond.amount.0;
+        let receiver = match status {
+            BondStatus::Refunded => Some(self.proposals[&proposal_id].owner.clone()),
+            _ => self.bond_treasury.clone(),
+        };
+        match receiver {
+            _ if amount == 0 => {}
+            Some(receiver) => {
+                log!("Sending Bond of {} on Proposal {} to {}", amount, proposal_id, receiver);
+                Promise::new(receiver).transfer(amount);
+            }
+            None => self.add_to_treasury(amount),
+        }
+    }
+
+    // The account and the accounts it delegates to in turn, in order. Chains
+    // never loop, as delegate() refuses to close one.
+    fn delegate_chain(&self, account_id: &AccountId) -> Vec<AccountId> {
+        let mut chain = vec![account_id.clone()];
+        while let Some(next) = self.delegations.get(chain.last().unwrap()) {
+            chain.push(next.clone());
+        }
+        chain
+    }
+
+    // Records the delegation
+    fn add_delegation(&mut self, delegator: AccountId, to: AccountId) {
+        self.delegators.entry(to.clone()).or_default().push(delegator.clone());
+        self.delegations.insert(delegator, to);
+    }
+
+    // Ends the account's delegation, if it has one
+    fn remove_delegation(&mut self, delegator: &AccountId) -> bool {
+        let Some(to) = self.delegations.remove(delegator) else { return false };
+        let delegators = self.delegators.get_mut(&to).unwrap();
+        delegators.retain(|account_id| account_id != delegator);
+        if delegators.is_empty() {
+            self.delegators.remove(&to);
+        }
+        true
+    }
+
+    // Every ballot counted when closing a proposal: the direct ones, plus on
+    // one-vote-per-account proposals a ballot for each account that did not
+    // vote but whose delegate chain reaches a voter. The delegators of each
+    // voter are walked back at most MAX_DELEGATION_DEPTH hops, stopping at
+    // accounts that voted themselves, and an account that may no longer vote
+    // passes nothing on.
+    fn counted_votes(&self, proposal_id: u128) -> Vec<Vote> {
+        let ballots = &self.proposal_votes[&proposal_id];
+        let mut votes: Vec<Vote> = ballots.values().cloned().collect();
+        if self.proposal_rules[&proposal_id].weighting != VoteWeighting::OnePerAccount {
+            return votes;
+        }
+        let mut delegated = 0;
+        for (voter, vote) in ballots.iter() {
+            let mut hop = vec![voter];
+            for _ in 0..MAX_DELEGATION_DEPTH {
+                let mut next_hop = Vec::new();
+                for account in hop {
+                    for delegator in self.delegators.get(account).into_iter().flatten() {
+                        if ballots.contains_key(delegator) {
+                            continue;
+                        }
+                        if self.is_permitted(delegator, Permission::Vote) {
+                            votes.push(Vote { ballot: vote.ballot.clone(), weight: U128(1), revision: vote.revision });
+                            delegated += 1;
+                        }
+                        next_hop.push(delegator);
+                    }
+                }
+                hop = next_hop;
+            }
+        }
+        if delegated > 0 {
+            log!("Counting {} Delegated Ballots on Proposal {}", delegated, proposal_id);
+        }
+        votes
+    }
+
+    // Voting weight per option of a proposal, in option order
+    fn tally(&self, proposal_id: u128) -> Vec<u128> {
+        let mut tally = vec![0; self.proposal_kinds[&proposal_id].option_count()];
+        for vote in self.proposal_votes[&proposal_id].values() {
+            tally[vote.ballot.tally_index()] += vote.weight.0;
+        }
+        tally
+    }
+}
+
+
+/*
+ * The rest of this file holds the inline tests for the code above
+ * Learn more about Rust tests: https://doc.rust-lang.org/book/ch11-01-writing-tests.html
+ */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::testing_env;
+    use near_sdk::test_utils::{get_logs, VMContextBuilder};
+    use near_sdk::{Balance, PromiseResult, RuntimeFeesConfig, VMConfig};
+
+    //const BENEFICIARY: &str = "beneficiary";
+    const NEAR: u128 = 1000000000000000000000000;
+    const VOTING_PERIOD: u64 = 1000;
+
+    #[test]
+    fn test_get_default_proposals() {
+        let contract = Contract::default();
+        // to check if the initialization is with no proposals already stored
+        assert_eq!(
+            contract.get_proposal_count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_create_new_proposal() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc: AccountId = "harry.near".parse().unwrap();
+        set_context(acc, 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+        assert_eq!(
+            contract.get_proposal_count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_vote_on_proposal() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1, 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        set_context(acc2, 10*NEAR);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        assert_eq!(
             1,
             1
         );
@@ -195,57 +1524,1826 @@ mod tests {
         // This is taken to imply success.
     }
 
+    #[test]
+    fn test_vote_again_replaces_earlier_choice() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1, 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        set_context(acc2.clone(), 10*NEAR);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        contract.vote_on_proposal(1, VoteChoice::No, None);
+        assert_eq!(
+            contract.get_all_votes(1),
+            vec![(acc2, Ballot::YesNo(VoteChoice::No))]
+        );
+    }
+
+    #[test]
+    fn test_close_proposal_counts_each_account_once() {
+        let mut contract = registered_contract(&["harry.near", "kurt.near", "weiler.near", "brandon.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1.clone(), 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+        let acc2: AccountId = "kurt.near".parse().unwrap();
+        set_context(acc2, 10*NEAR);
+        for _ in 0..3 {
+            contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        }
+        let acc3: AccountId = "weiler.near".parse().unwrap();
+        set_context(acc3, 10*NEAR);
+        contract.vote_on_proposal(1, VoteChoice::No, None);
+        let acc4: AccountId = "brandon.near".parse().unwrap();
+        set_context(acc4, 10*NEAR);
+        contract.vote_on_proposal(1, VoteChoice::No, None);
+        set_context(acc1, 10*NEAR);
+        let result = contract.close_proposal(1);
+        assert!(!result);
+    }
+
     #[test]
     fn test_close_proposal() {
-        let mut contract = Contract::default();
+        let mut contract = registered_contract(&[
+            "harry.near",
+            "kurt.near",
+            "weiler.near",
+            "brandon.near",
+            "snow.near",
+        ]);
         let acc1: AccountId = "harry.near".parse().unwrap();
         set_context(acc1.clone(), 10*NEAR);
-        contract.create_proposal("Should bears be legal pets?".to_string());
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
         let acc2: AccountId = "kurt.near".parse().unwrap();
         set_context(acc2, 10*NEAR);
-        contract.vote_on_proposal(1, true);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
         let acc3: AccountId = "weiler.near".parse().unwrap();
         set_context(acc3, 10*NEAR);
-        contract.vote_on_proposal(1, false);
+        contract.vote_on_proposal(1, VoteChoice::No, None);
         let acc4: AccountId = "brandon.near".parse().unwrap();
         set_context(acc4, 10*NEAR);
-        contract.vote_on_proposal(1, true);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
         let acc5: AccountId = "snow.near".parse().unwrap();
         set_context(acc5, 10*NEAR);
-        contract.vote_on_proposal(1, true);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
         set_context(acc1.clone(), 10*NEAR);
         let result = contract.close_proposal(1);
+        assert!(result);
+       
+    }
+
+    #[test]
+    fn test_state_persists_across_calls() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1, 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+        contract.create_proposal("Should owls deliver mail?".to_string(), None, None, None, None);
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        set_context(acc2.clone(), 10*NEAR);
+        contract.vote_on_proposal(2, VoteChoice::Yes, None);
+        env::state_write(&contract);
+        drop(contract);
+
+        // Reload the contract from storage, as happens between transactions
+        let contract: Contract = env::state_read().unwrap();
         assert_eq!(
-            result,
-            true
+            contract.get_all_proposals().len(),
+            2
+        );
+        assert!(contract.get_all_votes(1).is_empty());
+        assert_eq!(
+            contract.get_all_votes(2),
+            vec![(acc2, Ballot::YesNo(VoteChoice::Yes))]
         );
-       
     }
 
     #[test]
-    fn test_void_proposal() {
-        let mut contract = Contract::default();
+    fn test_voting_period_sets_deadline() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context_at(acc1, 10*NEAR, 1_000);
+        let rules = ProposalRules { voting_period: Some(U64(500)), ..Default::default() };
+        contract.create_proposal("Should bears be legal pets?".to_string(), Some(rules), None, None, None);
+        assert_eq!(
+            contract.get_proposal_rules(1).unwrap().voting_ends_at,
+            Some(U64(1_500))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Voting Period has Ended!")]
+    fn test_vote_after_deadline() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context_at(acc1, 10*NEAR, 1_000);
+        let rules = ProposalRules { voting_ends_at: Some(U64(2_000)), ..Default::default() };
+        contract.create_proposal("Should bears be legal pets?".to_string(), Some(rules), None, None, None);
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        set_context_at(acc2, 10*NEAR, 2_000);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_only_owner_closes_before_deadline() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context_at(acc1, 10*NEAR, 1_000);
+        let rules = ProposalRules { voting_ends_at: Some(U64(2_000)), ..Default::default() };
+        contract.create_proposal("Should bears be legal pets?".to_string(), Some(rules), None, None, None);
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        set_context_at(acc2, 10*NEAR, 1_500);
+        contract.close_proposal(1);
+    }
+
+    #[test]
+    fn test_anyone_finalizes_after_deadline() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context_at(acc1, 10*NEAR, 1_000);
+        let rules = ProposalRules { voting_ends_at: Some(U64(2_000)), ..Default::default() };
+        contract.create_proposal("Should bears be legal pets?".to_string(), Some(rules), None, None, None);
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        set_context_at(acc2.clone(), 10*NEAR, 1_500);
+        contract.vote_on_proposal(1, VoteChoice::No, None);
+        set_context_at(acc2, 10*NEAR, 2_500);
+        let result = contract.close_proposal(1);
+        assert!(!result);
+    }
+
+    #[test]
+    fn test_close_proposal_without_quorum() {
+        let mut contract = registered_contract(&["harry.near", "kurt.near"]);
         let acc1: AccountId = "harry.near".parse().unwrap();
         set_context(acc1.clone(), 10*NEAR);
-        contract.create_proposal("Should bears be legal pets?".to_string());
-        
+        let rules = ProposalRules { quorum: Some(Quorum::MinBallots(3)), ..Default::default() };
+        contract.create_proposal("Should bears be legal pets?".to_string(), Some(rules), None, None, None);
+        let acc2: AccountId = "kurt.near".parse().unwrap();
+        set_context(acc2, 10*NEAR);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        set_context(acc1, 10*NEAR);
+        let result = contract.close_proposal(1);
+        assert!(!result);
+        assert_eq!(
+            contract.get_proposal_outcome(1),
+            Some(ProposalOutcome::FailedQuorum)
+        );
+        assert_eq!(contract.get_failed_quorum_proposal_count(), 1);
+        assert_eq!(contract.get_rejected_proposal_count(), 0);
+    }
+
+    #[test]
+    fn test_close_proposal_with_quorum() {
+        let mut contract = registered_contract(&["harry.near", "kurt.near", "weiler.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
         set_context(acc1.clone(), 10*NEAR);
-        let result = contract.void_proposal(1);
+        let rules = ProposalRules { quorum: Some(Quorum::MinBallots(2)), ..Default::default() };
+        contract.create_proposal("Should bears be legal pets?".to_string(), Some(rules), None, None, None);
+        let acc2: AccountId = "kurt.near".parse().unwrap();
+        set_context(acc2, 10*NEAR);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        let acc3: AccountId = "weiler.near".parse().unwrap();
+        set_context(acc3, 10*NEAR);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        set_context(acc1, 10*NEAR);
+        let result = contract.close_proposal(1);
+        assert!(result);
+        assert_eq!(contract.get_successful_proposal_count(), 1);
+    }
+
+    #[test]
+    fn test_two_thirds_threshold() {
+        let mut contract = registered_contract(&["harry.near", "kurt.near", "weiler.near", "brandon.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1.clone(), 10*NEAR);
+        let rules = ProposalRules { threshold: Threshold::TwoThirds, ..Default::default() };
+        contract.create_proposal("Should bears be legal pets?".to_string(), Some(rules.clone()), None, None, None);
+        contract.create_proposal("Should owls deliver mail?".to_string(), Some(rules), None, None, None);
+        let voters = ["kurt.near", "weiler.near", "brandon.near"];
+        for (i, voter) in voters.iter().enumerate() {
+            set_context(voter.parse().unwrap(), 10*NEAR);
+            // 2 of 3 in favour of the first proposal, 1 of 3 of the second
+            contract.vote_on_proposal(1, (i < 2).into(), None);
+            contract.vote_on_proposal(2, (i < 1).into(), None);
+        }
+        set_context(acc1, 10*NEAR);
+        assert!(contract.close_proposal(1));
+        assert!(!contract.close_proposal(2));
         assert_eq!(
-            result,
-            true
+            contract.get_proposal_rules(1).unwrap().threshold,
+            Threshold::TwoThirds
         );
-       
     }
 
-    
+    #[test]
+    fn test_unanimous_threshold() {
+        let mut contract = registered_contract(&["harry.near", "kurt.near", "weiler.near", "brandon.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1.clone(), 10*NEAR);
+        let rules = ProposalRules { threshold: Threshold::Unanimous, ..Default::default() };
+        contract.create_proposal("Should bears be legal pets?".to_string(), Some(rules), None, None, None);
+        for (voter, choice) in [("kurt.near", true), ("weiler.near", true), ("brandon.near", false)] {
+            set_context(voter.parse().unwrap(), 10*NEAR);
+            contract.vote_on_proposal(1, choice.into(), None);
+        }
+        set_context(acc1, 10*NEAR);
+        assert!(!contract.close_proposal(1));
+    }
 
-    fn set_context(predecessor: AccountId, amount: Balance) {
-        let mut builder = VMContextBuilder::new();
-        
-        builder.predecessor_account_id(predecessor);
-        builder.attached_deposit(amount);
-    
-        testing_env!(builder.build());
-      }
+    #[test]
+    fn test_threshold_with_huge_weights() {
+        let rules = ProposalRules {
+            threshold: Threshold::Ratio { numerator: u32::MAX - 1, denominator: u32::MAX },
+            ..Default::default()
+        };
+        assert_eq!(rules.decide(u128::MAX, 0), Some(true));
+        let rules = ProposalRules { threshold: Threshold::Unanimous, ..Default::default() };
+        assert_eq!(rules.decide(u128::MAX - 1, 1), Some(false));
+        assert_eq!(ProposalRules::default().decide(u128::MAX / 2, u128::MAX / 2 + 1), Some(false));
+    }
+
+    #[test]
+    fn test_tie_fails_under_fail_policy() {
+        let mut contract = registered_contract(&["harry.near", "kurt.near", "weiler.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1.clone(), 10*NEAR);
+        let rules = ProposalRules { tie_policy: TiePolicy::Fail, ..Default::default() };
+        contract.create_proposal("Should bears be legal pets?".to_string(), Some(rules), None, None, None);
+        for (voter, choice) in [("kurt.near", true), ("weiler.near", false)] {
+            set_context(voter.parse().unwrap(), 10*NEAR);
+            contract.vote_on_proposal(1, choice.into(), None);
+        }
+        set_context(acc1, 10*NEAR);
+        assert!(!contract.close_proposal(1));
+        assert_eq!(
+            contract.get_proposal_outcome(1),
+            Some(ProposalOutcome::Rejected)
+        );
+    }
+
+    #[test]
+    fn test_tie_extends_voting() {
+        let mut contract = registered_contract(&["harry.near", "kurt.near", "weiler.near", "snow.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context_at(acc1, 10*NEAR, 1_000);
+        let rules = ProposalRules {
+            voting_period: Some(U64(500)),
+            tie_policy: TiePolicy::Extend,
+            ..Default::default()
+        };
+        contract.create_proposal("Should bears be legal pets?".to_string(), Some(rules), None, None, None);
+        for (voter, choice) in [("kurt.near", true), ("weiler.near", false)] {
+            set_context_at(voter.parse().unwrap(), 10*NEAR, 1_200);
+            contract.vote_on_proposal(1, choice.into(), None);
+        }
+        set_context_at("snow.near".parse().unwrap(), 10*NEAR, 1_600);
+        assert!(!contract.close_proposal(1));
+        assert_eq!(contract.get_proposal_outcome(1), None);
+        assert_eq!(
+            contract.get_proposal_rules(1).unwrap().voting_ends_at,
+            Some(U64(2_100))
+        );
+        // voting is open again, so the tie can be broken
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        set_context_at("snow.near".parse().unwrap(), 10*NEAR, 2_100);
+        assert!(contract.close_proposal(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Extending Voting on a Tie needs a Voting Period")]
+    fn test_tie_extension_needs_voting_period() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1, 10*NEAR);
+        let rules = ProposalRules { tie_policy: TiePolicy::Extend, ..Default::default() };
+        contract.create_proposal("Should bears be legal pets?".to_string(), Some(rules), None, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Threshold must be a Ratio between 0 and 1")]
+    fn test_invalid_ratio_threshold() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1, 10*NEAR);
+        let rules = ProposalRules {
+            threshold: Threshold::Ratio { numerator: 3, denominator: 2 },
+            ..Default::default()
+        };
+        contract.create_proposal("Should bears be legal pets?".to_string(), Some(rules), None, None, None);
+    }
+
+    #[test]
+    fn test_multiple_choice_proposal() {
+        let mut contract = registered_contract(&[
+            "harry.near",
+            "kurt.near",
+            "weiler.near",
+            "brandon.near",
+            "snow.near",
+        ]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1.clone(), 10*NEAR);
+        contract.create_proposal("Which vendor should cater?".to_string(), None, Some(vendor_poll()), None, None);
+        let votes = [("kurt.near", 2), ("weiler.near", 1), ("brandon.near", 2), ("snow.near", 0)];
+        for (voter, option_index) in votes {
+            set_context(voter.parse().unwrap(), 10*NEAR);
+            contract.vote_for_option(1, option_index);
+        }
+        assert_eq!(contract.get_tally(1), amounts(&[1, 1, 2]));
+        set_context(acc1, 10*NEAR);
+        assert!(contract.close_proposal(1));
+        assert_eq!(
+            contract.get_proposal_result(1),
+            Some(ProposalResult { tally: amounts(&[1, 1, 2]), winning_option: Some(2), unrevealed_commits: 0 })
+        );
+    }
+
+    #[test]
+    fn test_multiple_choice_tie_fails_under_fail_policy() {
+        let mut contract = registered_contract(&["harry.near", "kurt.near", "weiler.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1.clone(), 10*NEAR);
+        let rules = ProposalRules { tie_policy: TiePolicy::Fail, ..Default::default() };
+        contract.create_proposal("Which vendor should cater?".to_string(), Some(rules), Some(vendor_poll()), None, None);
+        for (voter, option_index) in [("kurt.near", 0), ("weiler.near", 1)] {
+            set_context(voter.parse().unwrap(), 10*NEAR);
+            contract.vote_for_option(1, option_index);
+        }
+        set_context(acc1, 10*NEAR);
+        assert!(!contract.close_proposal(1));
+        assert_eq!(contract.get_proposal_result(1).unwrap().winning_option, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Option does not Exist")]
+    fn test_vote_for_missing_option() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1, 10*NEAR);
+        contract.create_proposal("Which vendor should cater?".to_string(), None, Some(vendor_poll()), None, None);
+        contract.vote_for_option(1, 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Proposal is not a Yes/No Proposal")]
+    fn test_yes_no_vote_on_multiple_choice() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1, 10*NEAR);
+        contract.create_proposal("Which vendor should cater?".to_string(), None, Some(vendor_poll()), None, None);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+    }
+
+    #[test]
+    fn test_ranked_choice_proposal() {
+        let mut contract = registered_contract(&[
+            "harry.near",
+            "kurt.near",
+            "weiler.near",
+            "brandon.near",
+            "snow.near",
+            "mikky.near",
+        ]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1.clone(), 10*NEAR);
+        let kind = ProposalKind::RankedChoice { options: vendor_poll().options().unwrap().clone() };
+        contract.create_proposal("Which vendor should cater?".to_string(), None, Some(kind), None, None);
+        let rankings = [
+            ("kurt.near", vec![0, 2]),
+            ("weiler.near", vec![0]),
+            ("brandon.near", vec![1, 0]),
+            ("snow.near", vec![1]),
+            ("mikky.near", vec![2, 1, 0]),
+        ];
+        for (voter, ranking) in rankings {
+            set_context(voter.parse().unwrap(), 10*NEAR);
+            contract.vote_ranked(1, ranking);
+        }
+        set_context(acc1, 10*NEAR);
+        assert!(contract.close_proposal(1));
+        assert_eq!(
+            contract.get_runoff_rounds(1),
+            vec![
+                RunoffRound { totals: amounts(&[2, 2, 1]), eliminated: Some(2) },
+                RunoffRound { totals: amounts(&[2, 3, 0]), eliminated: None },
+            ]
+        );
+        assert_eq!(contract.get_proposal_result(1).unwrap().winning_option, Some(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Option Ranked more than Once")]
+    fn test_ranking_repeats_option() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1, 10*NEAR);
+        let kind = ProposalKind::RankedChoice { options: vendor_poll().options().unwrap().clone() };
+        contract.create_proposal("Which vendor should cater?".to_string(), None, Some(kind), None, None);
+        contract.vote_ranked(1, vec![1, 0, 1]);
+    }
+
+    #[test]
+    fn test_token_weighted_votes() {
+        let mut contract = registered_contract(&["harry.near", "kurt.near", "weiler.near", "brandon.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1.clone(), 10*NEAR);
+        let rules = ProposalRules {
+            weighting: VoteWeighting::TokenBalance { token_id: "token.near".parse().unwrap() },
+            ..Default::default()
+        };
+        contract.create_proposal("Should bears be legal pets?".to_string(), Some(rules), None, None, None);
+        // each vote first asks the token for the voter's balance
+        let balances = [("kurt.near", true, 100), ("weiler.near", false, 30), ("brandon.near", false, 40)];
+        for (voter, choice, _) in balances {
+            set_context(voter.parse().unwrap(), 10*NEAR);
+            assert!(matches!(contract.vote_on_proposal(1, choice.into(), None), PromiseOrValue::Promise(_)));
+        }
+        assert!(contract.get_all_votes(1).is_empty());
+        for (voter, choice, balance) in balances {
+            set_callback_context();
+            contract.on_vote_weight(1, voter.parse().unwrap(), Ballot::YesNo(choice.into()), Ok(U128(balance)));
+        }
+        assert_eq!(
+            contract.get_vote(1, "kurt.near".parse().unwrap()),
+            Some(Vote { ballot: Ballot::YesNo(VoteChoice::Yes), weight: U128(100), revision: 0 })
+        );
+        assert_eq!(contract.get_tally(1), amounts(&[100, 70, 0]));
+        set_context(acc1, 10*NEAR);
+        assert!(contract.close_proposal(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Voter holds no Voting Tokens")]
+    fn test_token_weighted_vote_without_balance() {
+        let mut contract = registered_contract(&["harry.near", "kurt.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1, 10*NEAR);
+        let rules = ProposalRules {
+            weighting: VoteWeighting::TokenBalance { token_id: "token.near".parse().unwrap() },
+            ..Default::default()
+        };
+        contract.create_proposal("Should bears be legal pets?".to_string(), Some(rules), None, None, None);
+        set_callback_context();
+        contract.on_vote_weight(1, "kurt.near".parse().unwrap(), Ballot::YesNo(VoteChoice::Yes), Ok(U128(0)));
+    }
+
+    #[test]
+    fn test_stake_weighted_votes() {
+        let mut contract = registered_contract(&["harry.near", "kurt.near", "weiler.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1.clone(), 10*NEAR);
+        let rules = ProposalRules { weighting: VoteWeighting::Stake, ..Default::default() };
+        contract.create_proposal("Should bears be legal pets?".to_string(), Some(rules.clone()), None, None, None);
+        contract.create_proposal("Should owls deliver mail?".to_string(), Some(rules), None, None, None);
+        let acc2: AccountId = "kurt.near".parse().unwrap();
+        set_context(acc2.clone(), 5*NEAR);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        contract.vote_on_proposal(2, VoteChoice::Yes, None);
+        // changing the vote with more NEAR attached adds to the stake
+        set_context(acc2.clone(), 2*NEAR);
+        contract.vote_on_proposal(1, VoteChoice::No, None);
+        let acc3: AccountId = "weiler.near".parse().unwrap();
+        set_context(acc3, 3*NEAR);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+
+        assert_eq!(contract.get_tally(1), amounts(&[3*NEAR, 7*NEAR, 0]));
+        assert_eq!(contract.get_locked_stake(1, acc2.clone()), U128(7*NEAR));
+        assert_eq!(contract.get_proposal_locked_stake(1), U128(10*NEAR));
+        assert_eq!(contract.get_account_locked_stake(acc2.clone()), U128(12*NEAR));
+        set_context(acc1, 0);
+        assert!(!contract.close_proposal(1));
+
+        set_context(acc2.clone(), 0);
+        contract.claim_stake(1);
+        assert_eq!(contract.get_locked_stake(1, acc2.clone()), U128(0));
+        assert_eq!(contract.get_proposal_locked_stake(1), U128(3*NEAR));
+        assert_eq!(contract.get_account_locked_stake(acc2), U128(5*NEAR));
+    }
+
+    #[test]
+    #[should_panic(expected = "Proposal is Still Open")]
+    fn test_claim_stake_while_open() {
+        let mut contract = registered_contract(&["harry.near", "kurt.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1, 10*NEAR);
+        let rules = ProposalRules { weighting: VoteWeighting::Stake, ..Default::default() };
+        contract.create_proposal("Should bears be legal pets?".to_string(), Some(rules), None, None, None);
+        let acc2: AccountId = "kurt.near".parse().unwrap();
+        set_context(acc2.clone(), 5*NEAR);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        set_context(acc2, 0);
+        contract.claim_stake(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Attach a Deposit to Stake on this Proposal")]
+    fn test_stake_vote_without_deposit() {
+        let mut contract = registered_contract(&["harry.near", "kurt.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1, 10*NEAR);
+        let rules = ProposalRules { weighting: VoteWeighting::Stake, ..Default::default() };
+        contract.create_proposal("Should bears be legal pets?".to_string(), Some(rules), None, None, None);
+        let acc2: AccountId = "kurt.near".parse().unwrap();
+        set_context(acc2, 0);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+    }
+
+    #[test]
+    fn test_claim_stake_after_void() {
+        let mut contract = registered_contract(&["harry.near", "kurt.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1.clone(), 10*NEAR);
+        let rules = ProposalRules { weighting: VoteWeighting::Stake, ..Default::default() };
+        contract.create_proposal("Should bears be legal pets?".to_string(), Some(rules), None, None, None);
+        let acc2: AccountId = "kurt.near".parse().unwrap();
+        set_context(acc2.clone(), 5*NEAR);
+        contract.vote_on_proposal(1, VoteChoice::No, None);
+        set_context(acc1, 0);
+        assert!(contract.void_proposal(1));
+        set_context(acc2.clone(), 0);
+        contract.claim_stake(1);
+        assert_eq!(contract.get_account_locked_stake(acc2), U128(0));
+    }
+
+    #[test]
+    fn test_quadratic_votes() {
+        let mut contract = registered_contract(&[
+            "harry.near",
+            "kurt.near",
+            "weiler.near",
+            "brandon.near",
+            "snow.near",
+        ]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1.clone(), 10*NEAR);
+        let rules = ProposalRules { weighting: VoteWeighting::Quadratic { credits: 100 }, ..Default::default() };
+        contract.create_proposal("Should bears be legal pets?".to_string(), Some(rules), None, None, None);
+        let acc2: AccountId = "kurt.near".parse().unwrap();
+        set_context(acc2.clone(), 0);
+        contract.vote_on_proposal(1, VoteChoice::Yes, Some(10));
+        assert_eq!(contract.get_voice_credits(1, acc2.clone()), 0);
+        // lowering the magnitude refunds the credits of the earlier ballot
+        contract.vote_on_proposal(1, VoteChoice::Yes, Some(5));
+        assert_eq!(contract.get_voice_credits(1, acc2), 75);
+        for voter in ["weiler.near", "brandon.near", "snow.near"] {
+            set_context(voter.parse().unwrap(), 0);
+            contract.vote_on_proposal(1, VoteChoice::No, Some(2));
+        }
+        assert_eq!(contract.get_tally(1), amounts(&[5, 6, 0]));
+        set_context(acc1, 0);
+        assert!(!contract.close_proposal(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Not Enough Voice Credits")]
+    fn test_quadratic_vote_over_budget() {
+        let mut contract = registered_contract(&["harry.near", "kurt.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1, 10*NEAR);
+        let rules = ProposalRules { weighting: VoteWeighting::Quadratic { credits: 100 }, ..Default::default() };
+        contract.create_proposal("Should bears be legal pets?".to_string(), Some(rules), None, None, None);
+        set_context("kurt.near".parse().unwrap(), 0);
+        contract.vote_on_proposal(1, VoteChoice::Yes, Some(11));
+    }
+
+    #[test]
+    #[should_panic(expected = "Vote Magnitude only applies to Quadratic Proposals")]
+    fn test_magnitude_on_plain_proposal() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1, 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+        contract.vote_on_proposal(1, VoteChoice::Yes, Some(3));
+    }
+
+    fn secret_rules() -> ProposalRules {
+        let secret_ballot = SecretBallot { commit_ends_at: U64(1_000), reveal_ends_at: U64(2_000) };
+        ProposalRules { secret_ballot: Some(secret_ballot), ..Default::default() }
+    }
+
+    fn commitment(vote_choice: VoteChoice, salt: &str) -> Base64VecU8 {
+        Base64VecU8(env::sha256(&[&[u8::from(vote_choice)], salt.as_bytes()].concat()))
+    }
+
+    #[test]
+    fn test_secret_ballot_commit_and_reveal() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near", "lucy.near", "omar.near"]);
+        let acc0: AccountId = "contract.near".parse().unwrap();
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        let acc3: AccountId = "lucy.near".parse().unwrap();
+        let acc4: AccountId = "omar.near".parse().unwrap();
+        set_context_at(acc1, 10*NEAR, 0);
+        contract.create_proposal("Should pineapple go on pizza?".to_string(), Some(secret_rules()), None, None, None);
+        set_context_at(acc2.clone(), 10*NEAR, 100);
+        contract.commit_vote(1, commitment(VoteChoice::Yes, "anchovies"));
+        set_context_at(acc3.clone(), 10*NEAR, 200);
+        contract.commit_vote(1, commitment(VoteChoice::No, "olives"));
+        set_context_at(acc4.clone(), 10*NEAR, 300);
+        contract.commit_vote(1, commitment(VoteChoice::No, "basil"));
+        // nothing is counted until ballots are revealed
+        assert_eq!(contract.get_tally(1), amounts(&[0, 0, 0]));
+        assert_eq!(contract.get_unrevealed_commits(1).len(), 3);
+
+        set_context_at(acc2, 10*NEAR, 1_000);
+        contract.reveal_vote(1, VoteChoice::Yes, "anchovies".to_string());
+        set_context_at(acc3, 10*NEAR, 1_500);
+        contract.reveal_vote(1, VoteChoice::No, "olives".to_string());
+        assert_eq!(contract.get_tally(1), amounts(&[1, 1, 0]));
+        assert_eq!(contract.get_unrevealed_commits(1), vec![acc4]);
+
+        // the unrevealed ballot is left out, and the tie passes by default
+        set_context_at(acc0, 10*NEAR, 2_000);
+        assert!(contract.close_proposal(1));
+        assert_eq!(contract.get_proposal_result(1).unwrap().unrevealed_commits, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Ballot does not Match its Commitment")]
+    fn test_secret_ballot_reveal_must_match() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        set_context_at(acc1, 10*NEAR, 0);
+        contract.create_proposal("Should pineapple go on pizza?".to_string(), Some(secret_rules()), None, None, None);
+        set_context_at(acc2.clone(), 10*NEAR, 100);
+        contract.commit_vote(1, commitment(VoteChoice::Yes, "anchovies"));
+        set_context_at(acc2, 10*NEAR, 1_000);
+        contract.reveal_vote(1, VoteChoice::No, "anchovies".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Commit Window is Still Open")]
+    fn test_secret_ballot_reveal_waits_for_commits() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        set_context_at(acc1, 10*NEAR, 0);
+        contract.create_proposal("Should pineapple go on pizza?".to_string(), Some(secret_rules()), None, None, None);
+        set_context_at(acc2, 10*NEAR, 100);
+        contract.commit_vote(1, commitment(VoteChoice::Yes, "anchovies"));
+        contract.reveal_vote(1, VoteChoice::Yes, "anchovies".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Proposal uses Secret Ballots; Commit your Vote Instead")]
+    fn test_secret_ballot_rejects_open_votes() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context_at(acc1, 10*NEAR, 0);
+        contract.create_proposal("Should pineapple go on pizza?".to_string(), Some(secret_rules()), None, None, None);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+    }
+
+    #[test]
+    fn test_delegated_votes_count_at_close() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near", "lucy.near", "omar.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        let acc3: AccountId = "lucy.near".parse().unwrap();
+        let acc4: AccountId = "omar.near".parse().unwrap();
+        set_context(acc1.clone(), 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+        // lucy hands her vote to omar, who hands his to mikky
+        set_context(acc3.clone(), 10*NEAR);
+        contract.delegate(acc4.clone());
+        set_context(acc4.clone(), 10*NEAR);
+        contract.delegate(acc2.clone());
+        set_context(acc2.clone(), 10*NEAR);
+        contract.vote_on_proposal(1, VoteChoice::No, None);
+        set_context(acc1.clone(), 10*NEAR);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        // delegated ballots are only counted when the proposal closes
+        assert_eq!(contract.get_tally(1), amounts(&[1, 1, 0]));
+        assert!(!contract.close_proposal(1));
+        assert_eq!(contract.get_proposal_result(1).unwrap().tally, amounts(&[1, 3, 0]));
+
+        // a direct ballot overrides the delegation, and undelegating ends it
+        contract.create_proposal("Should owls deliver mail?".to_string(), None, None, None, None);
+        set_context(acc2, 10*NEAR);
+        contract.vote_on_proposal(2, VoteChoice::No, None);
+        set_context(acc4.clone(), 10*NEAR);
+        contract.vote_on_proposal(2, VoteChoice::Yes, None);
+        set_context(acc3, 10*NEAR);
+        contract.undelegate();
+        set_context(acc1, 10*NEAR);
+        assert!(contract.close_proposal(2));
+        assert_eq!(contract.get_proposal_result(2).unwrap().tally, amounts(&[1, 1, 0]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Delegation would Form a Cycle")]
+    fn test_delegation_cycle_is_refused() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near", "lucy.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        let acc3: AccountId = "lucy.near".parse().unwrap();
+        set_context(acc1.clone(), 0);
+        contract.delegate(acc2.clone());
+        set_context(acc2, 0);
+        contract.delegate(acc3.clone());
+        set_context(acc3, 0);
+        contract.delegate(acc1);
+    }
+
+    #[test]
+    fn test_delegation_is_charged_to_delegator() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        let available_before = contract.storage_balance_of(acc1.clone()).unwrap().available.0;
+        set_context(acc1.clone(), 0);
+        contract.delegate(acc2);
+        let available_delegating = contract.storage_balance_of(acc1.clone()).unwrap().available.0;
+        assert!(available_delegating < available_before);
+        // the freed bytes are credited back, except for the emptied slot the delegation map keeps
+        contract.undelegate();
+        assert!(contract.storage_balance_of(acc1).unwrap().available.0 > available_delegating);
+    }
+
+    #[test]
+    fn test_delegating_again_moves_the_vote() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near", "lucy.near", "omar.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        let acc3: AccountId = "lucy.near".parse().unwrap();
+        let acc4: AccountId = "omar.near".parse().unwrap();
+        set_context(acc1.clone(), 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+        // omar's vote goes through harry, who moves from mikky to lucy
+        set_context(acc4, 0);
+        contract.delegate(acc1.clone());
+        set_context(acc1.clone(), 0);
+        contract.delegate(acc2.clone());
+        contract.delegate(acc3.clone());
+        assert_eq!(contract.get_delegate(acc1.clone()), Some(acc3.clone()));
+        set_context(acc2, 0);
+        contract.vote_on_proposal(1, VoteChoice::No, None);
+        set_context(acc3, 0);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        set_context(acc1, 0);
+        assert!(contract.close_proposal(1));
+        assert_eq!(contract.get_proposal_result(1).unwrap().tally, amounts(&[3, 1, 0]));
+    }
+
+    #[test]
+    fn test_delegation_needs_vote_permission_at_close() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near", "lucy.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        let acc3: AccountId = "lucy.near".parse().unwrap();
+        set_context(acc1.clone(), 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        set_context(acc3.clone(), 10*NEAR);
+        contract.delegate(acc2.clone());
+        set_context(acc2, 10*NEAR);
+        contract.vote_on_proposal(1, VoteChoice::No, None);
+        // lucy becomes an observer after delegating, so her vote is not passed on
+        set_callback_context();
+        contract.add_member(acc3, Role::Observer);
+        set_context(acc1, 10*NEAR);
+        assert!(contract.close_proposal(1));
+        assert_eq!(contract.get_proposal_result(1).unwrap().tally, amounts(&[1, 1, 0]));
+    }
+
+    #[test]
+    fn test_member_registry_roles() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near", "lucy.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        let acc3: AccountId = "lucy.near".parse().unwrap();
+        set_callback_context();
+        contract.add_member(acc1.clone(), Role::Council);
+        contract.add_member(acc2.clone(), Role::Member);
+        contract.add_member(acc3.clone(), Role::Observer);
+        contract.set_public_permissions(Vec::new());
+        assert_eq!(contract.get_members_by_role(Role::Council), vec![acc1.clone()]);
+        assert_eq!(contract.get_member_role(acc3.clone()), Some(Role::Observer));
+
+        set_context(acc2.clone(), 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        set_context(acc1, 10*NEAR);
+        contract.vote_on_proposal(1, VoteChoice::No, None);
+        // a member may close their own proposal, but voiding is left to the council
+        set_context(acc2, 10*NEAR);
+        assert!(contract.close_proposal(1));
+        assert!(!contract.get_role_permissions(Role::Member).contains(&Permission::Void));
+    }
+
+    #[test]
+    #[should_panic(expected = "Account is not Permitted to Vote")]
+    fn test_observer_cannot_vote() {
+        let mut contract = registered_contract(&["harry.near", "lucy.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "lucy.near".parse().unwrap();
+        set_callback_context();
+        contract.add_member(acc2.clone(), Role::Observer);
+        set_context(acc1, 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+        set_context(acc2, 10*NEAR);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Account is not Permitted to Propose")]
+    fn test_public_permissions_gate_non_members() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_callback_context();
+        contract.set_public_permissions(vec![Permission::Vote]);
+        set_context(acc1, 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the Admin or Governance can Manage Members")]
+    fn test_only_admin_manages_members() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1.clone(), 10*NEAR);
+        contract.add_member(acc1, Role::Council);
+    }
+
+    #[test]
+    fn test_proposal_bond_refunded_on_close() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1, 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+        let bond = ProposalBond { amount: U128(NEAR), status: BondStatus::Held };
+        assert_eq!(contract.get_proposal_bond(1), Some(bond));
+        // the bond comes back whatever the outcome
+        contract.vote_on_proposal(1, VoteChoice::No, None);
+        assert!(!contract.close_proposal(1));
+        assert_eq!(contract.get_proposal_bond(1).unwrap().status, BondStatus::Refunded);
+    }
+
+    #[test]
+    fn test_proposal_bond_slashed_by_moderation() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        let treasury: AccountId = "treasury.near".parse().unwrap();
+        set_callback_context();
+        contract.add_member(acc2.clone(), Role::Council);
+        contract.set_bond_treasury(Some(treasury.clone()));
+        contract.set_proposal_bond_amount(U128(2*NEAR));
+        set_context(acc1, 10*NEAR);
+        contract.create_proposal("Buy cheap pills online!".to_string(), None, None, None, None);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        set_context(acc2, 0);
+        contract.moderate_proposal(1);
+        assert_eq!(contract.get_proposal_status(1), Some(ProposalStatus::Voided));
+        assert_eq!(contract.get_proposal_outcome(1), None);
+        assert_eq!(
+            contract.get_proposal_bond(1),
+            Some(ProposalBond { amount: U128(2*NEAR), status: BondStatus::Slashed })
+        );
+        assert_eq!(contract.get_bond_treasury(), Some(treasury));
+    }
+
+    #[test]
+    #[should_panic(expected = "Attach a Bond of 1000000000000000000000000 yoctoNEAR to Create a Proposal")]
+    fn test_create_proposal_without_bond() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1, 0);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Account is not Permitted to Moderate")]
+    fn test_only_council_moderates() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1, 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+        contract.moderate_proposal(1);
+    }
+
+    #[test]
+    fn test_storage_charged_for_proposals_and_votes() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        let registered = contract.storage_balance_of(acc2.clone()).unwrap();
+        assert_eq!(registered.total, U128(NEAR));
+        set_context(acc1.clone(), 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+        set_context(acc2.clone(), 0);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        let voted = contract.storage_balance_of(acc2.clone()).unwrap();
+        assert!(voted.available.0 < registered.available.0);
+        // changing the ballot to one of the same size costs nothing more
+        contract.vote_on_proposal(1, VoteChoice::No, None);
+        assert_eq!(contract.storage_balance_of(acc2.clone()), Some(voted.clone()));
+
+        let mut builder = VMContextBuilder::new();
+        builder.predecessor_account_id(acc2.clone());
+        builder.attached_deposit(1);
+        testing_env!(builder.build());
+        let withdrawn = contract.storage_withdraw(None);
+        assert_eq!(withdrawn.available, U128(0));
+        assert_eq!(withdrawn.total.0, voted.total.0 - voted.available.0);
+        assert!(contract.storage_balance_of(acc1).unwrap().available.0 < NEAR);
+    }
+
+    #[test]
+    #[should_panic(expected = "Account has no Storage Balance; call storage_deposit First")]
+    fn test_vote_without_storage_deposit() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1, 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        set_context(acc2, 0);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not Enough Storage Balance")]
+    fn test_create_proposal_over_storage_balance() {
+        let mut contract = Contract::default();
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let min = contract.storage_balance_bounds().min;
+        set_context(acc1.clone(), min.0);
+        contract.storage_deposit(None, Some(true));
+        set_context(acc1, 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+    }
+
+    #[test]
+    fn test_proposal_metadata() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1.clone(), 10*NEAR);
+        let metadata = ProposalMetadata {
+            body: "Bears make loyal companions.".to_string(),
+            link: Some("https://example.org/bears".to_string()),
+            tags: vec!["wildlife".to_string()],
+            content_hash: Some(Base64VecU8(env::sha256(b"Bears make loyal companions."))),
+        };
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, Some(metadata.clone()), None);
+        let proposal = contract.get_proposal(1).unwrap();
+        assert_eq!(proposal.title, "Should bears be legal pets?");
+        assert_eq!(proposal.body, metadata.body);
+        assert_eq!(proposal.link, metadata.link);
+        assert_eq!(proposal.tags, metadata.tags);
+        assert_eq!(proposal.content_hash, metadata.content_hash);
+        assert_eq!(proposal.owner, acc1);
+        assert_eq!(proposal.created_at, U64(env::block_timestamp()));
+        assert_eq!(contract.get_proposal(2), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Content Hash must be a SHA-256 Hash")]
+    fn test_proposal_content_hash_length() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1, 10*NEAR);
+        let metadata = ProposalMetadata { content_hash: Some(Base64VecU8(vec![1, 2, 3])), ..Default::default() };
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, Some(metadata), None);
+    }
+
+    #[test]
+    fn test_passed_proposal_runs_actions() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        let action = ProposalAction {
+            receiver_id: "receiver.near".parse().unwrap(),
+            method_name: "record".to_string(),
+            args: Base64VecU8(b"{\"message\":\"hello\"}".to_vec()),
+            deposit: U128(0),
+            gas: U64(20_000_000_000_000),
+        };
+        let other_action = ProposalAction { receiver_id: "other.near".parse().unwrap(), ..action.clone() };
+        let kind = ProposalKind::Actions { actions: vec![action.clone(), action, other_action] };
+        set_context(acc1.clone(), 10*NEAR);
+        contract.create_proposal("Should we say hello?".to_string(), executing_rules(), Some(kind), None, None);
+        set_context(acc2, 0);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        assert_eq!(contract.get_action_statuses(1), None);
+        set_context_at(acc1, 0, VOTING_PERIOD);
+        assert!(contract.close_proposal(1));
+        assert_eq!(contract.get_action_statuses(1), Some(vec![ActionStatus::Pending; 3]));
+
+        // the actions on receiver.near run as one batch, the one on other.near as another
+        set_callback_result(PromiseResult::Successful(Vec::new()));
+        assert_eq!(contract.on_actions_executed(1, 0, 2), ActionStatus::Succeeded);
+        assert_eq!(contract.get_proposal_status(1), Some(ProposalStatus::Passed));
+        set_callback_result(PromiseResult::Failed);
+        assert_eq!(contract.on_actions_executed(1, 2, 1), ActionStatus::Failed);
+        assert_eq!(contract.get_proposal_status(1), Some(ProposalStatus::Executed));
+        assert_eq!(
+            contract.get_action_statuses(1),
+            Some(vec![ActionStatus::Succeeded, ActionStatus::Succeeded, ActionStatus::Failed])
+        );
+    }
+
+    #[test]
+    fn test_action_deposits_come_from_treasury() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        set_context(acc2.clone(), 5*NEAR);
+        contract.fund_treasury();
+        let action = ProposalAction {
+            receiver_id: "receiver.near".parse().unwrap(),
+            method_name: "record".to_string(),
+            args: Base64VecU8(b"{\"message\":\"hello\"}".to_vec()),
+            deposit: U128(2*NEAR),
+            gas: U64(20_000_000_000_000),
+        };
+        let kind = ProposalKind::Actions { actions: vec![action.clone(), action] };
+        set_context(acc1.clone(), 10*NEAR);
+        contract.create_proposal("Should we say hello?".to_string(), executing_rules(), Some(kind.clone()), None, None);
+        assert_eq!(contract.get_treasury()[0].committed, U128(4*NEAR));
+
+        // a rejected proposal releases its deposits
+        set_context(acc2.clone(), 0);
+        contract.vote_on_proposal(1, VoteChoice::No, None);
+        set_context_at(acc1.clone(), 0, VOTING_PERIOD);
+        assert!(!contract.close_proposal(1));
+        assert_eq!(contract.get_treasury()[0].committed, U128(0));
+
+        // a passed one spends them, and gets the deposits of a failed batch back
+        set_context(acc1.clone(), 10*NEAR);
+        contract.create_proposal("Should we say hello again?".to_string(), executing_rules(), Some(kind), None, None);
+        set_context(acc2, 0);
+        contract.vote_on_proposal(2, VoteChoice::Yes, None);
+        set_context_at(acc1, 0, VOTING_PERIOD);
+        assert!(contract.close_proposal(2));
+        assert_eq!(contract.get_treasury()[0].balance, U128(NEAR));
+        assert_eq!(contract.get_treasury()[0].committed, U128(0));
+        set_callback_result(PromiseResult::Failed);
+        contract.on_actions_executed(2, 0, 2);
+        assert_eq!(contract.get_treasury()[0].balance, U128(5*NEAR));
+        assert_eq!(contract.get_action_statuses(2), Some(vec![ActionStatus::Failed; 2]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Treasury cannot Cover the Action Deposits: 1000000000000000000000000 Available")]
+    fn test_action_deposits_over_treasury() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1.clone(), NEAR);
+        contract.fund_treasury();
+        let action = ProposalAction {
+            receiver_id: "receiver.near".parse().unwrap(),
+            method_name: "record".to_string(),
+            args: Base64VecU8(Vec::new()),
+            deposit: U128(2*NEAR),
+            gas: U64(20_000_000_000_000),
+        };
+        set_context(acc1, 10*NEAR);
+        let kind = ProposalKind::Actions { actions: vec![action] };
+        contract.create_proposal("Should we say hello?".to_string(), executing_rules(), Some(kind), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the Admin or the Council can Propose Actions on the Contract")]
+    fn test_actions_on_the_contract_proposed_by_council_only() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let action = ProposalAction {
+            receiver_id: env::current_account_id(),
+            method_name: "set_admin".to_string(),
+            args: Base64VecU8(b"{\"account_id\":\"harry.near\"}".to_vec()),
+            deposit: U128(0),
+            gas: U64(20_000_000_000_000),
+        };
+        set_context(acc1, 10*NEAR);
+        let kind = ProposalKind::Actions { actions: vec![action] };
+        contract.create_proposal("Should harry run the DAO?".to_string(), executing_rules(), Some(kind), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "A Proposal with Actions needs 1 to 10 of them")]
+    fn test_actions_proposal_needs_actions() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1, 10*NEAR);
+        let kind = ProposalKind::Actions { actions: Vec::new() };
+        contract.create_proposal("Should we do nothing?".to_string(), None, Some(kind), None, None);
+    }
+
+    #[test]
+    fn test_transfer_proposal_pays_out_of_treasury() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        set_context(acc2.clone(), 5*NEAR);
+        contract.fund_treasury();
+        let transfer = TreasuryTransfer { receiver_id: acc2.clone(), amount: U128(3*NEAR), token_id: None };
+        set_context(acc1.clone(), 10*NEAR);
+        let kind = ProposalKind::Transfer(transfer.clone());
+        contract.create_proposal("Should we pay mikky?".to_string(), executing_rules(), Some(kind), None, None);
+        assert_eq!(contract.get_pending_transfers(), vec![(1, transfer.clone())]);
+        assert_eq!(
+            contract.get_treasury(),
+            vec![TreasuryBalance { token_id: None, balance: U128(5*NEAR), committed: U128(3*NEAR) }]
+        );
+        set_context(acc2, 0);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        set_context_at(acc1, 0, VOTING_PERIOD);
+        assert!(contract.close_proposal(1));
+        assert_eq!(contract.get_pending_transfers(), Vec::new());
+        assert_eq!(
+            contract.get_treasury(),
+            vec![TreasuryBalance { token_id: None, balance: U128(2*NEAR), committed: U128(0) }]
+        );
+
+        // a failed payout goes back into the treasury
+        set_callback_result(PromiseResult::Failed);
+        assert!(!contract.on_treasury_payout(1, transfer));
+        assert_eq!(contract.get_treasury()[0].balance, U128(5*NEAR));
+        assert_eq!(contract.get_proposal_status(1), Some(ProposalStatus::Passed));
+    }
+
+    #[test]
+    #[should_panic(expected = "Treasury cannot Cover the Transfer")]
+    fn test_transfer_proposals_cannot_overcommit() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1.clone(), 5*NEAR);
+        contract.fund_treasury();
+        let transfer = TreasuryTransfer { receiver_id: acc1.clone(), amount: U128(3*NEAR), token_id: None };
+        set_context(acc1, 10*NEAR);
+        let kind = ProposalKind::Transfer(transfer.clone());
+        contract.create_proposal("Should we pay harry?".to_string(), executing_rules(), Some(kind), None, None);
+        let kind = ProposalKind::Transfer(transfer);
+        contract.create_proposal("Should we pay harry again?".to_string(), executing_rules(), Some(kind), None, None);
+    }
+
+    #[test]
+    fn test_voided_transfer_releases_tokens() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let token: AccountId = "token.near".parse().unwrap();
+        set_callback_context();
+        contract.register_treasury_token(token.clone());
+        set_context(token.clone(), 0);
+        contract.ft_on_transfer(acc1.clone(), U128(100), String::new());
+        let transfer = TreasuryTransfer {
+            receiver_id: acc1.clone(),
+            amount: U128(60),
+            token_id: Some(token.clone()),
+        };
+        set_context(acc1, 10*NEAR);
+        let kind = ProposalKind::Transfer(transfer);
+        contract.create_proposal("Should we pay harry tokens?".to_string(), executing_rules(), Some(kind), None, None);
+        assert_eq!(contract.get_treasury()[1].committed, U128(60));
+        assert!(contract.void_proposal(1));
+        // the tokens are free again, and the slashed bond joins the treasury
+        assert_eq!(
+            contract.get_treasury(),
+            vec![
+                TreasuryBalance { token_id: None, balance: U128(NEAR), committed: U128(0) },
+                TreasuryBalance { token_id: Some(token), balance: U128(100), committed: U128(0) },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_transfer_proposal_needs_the_execution_quorum() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        set_context(acc2, 5*NEAR);
+        contract.fund_treasury();
+        set_callback_context();
+        contract.set_execution_quorum(2);
+        let transfer = TreasuryTransfer { receiver_id: acc1.clone(), amount: U128(5*NEAR), token_id: None };
+        // the proposer cannot lower the quorum the admin set
+        let rules = ProposalRules {
+            voting_period: Some(U64(VOTING_PERIOD)),
+            quorum: Some(Quorum::MinBallots(0)),
+            ..Default::default()
+        };
+        set_context(acc1.clone(), 10*NEAR);
+        let kind = ProposalKind::Transfer(transfer);
+        contract.create_proposal("Should we pay harry?".to_string(), Some(rules), Some(kind), None, None);
+        assert_eq!(contract.get_proposal_rules(1).unwrap().quorum, Some(Quorum::MinBallots(2)));
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        set_context_at(acc1, 0, VOTING_PERIOD);
+        assert!(!contract.close_proposal(1));
+        assert_eq!(contract.get_proposal_status(1), Some(ProposalStatus::Expired));
+        assert_eq!(
+            contract.get_treasury(),
+            vec![TreasuryBalance { token_id: None, balance: U128(5*NEAR), committed: U128(0) }]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Proposals that Execute cannot be Closed before Voting Ends")]
+    fn test_transfer_proposal_cannot_close_early() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1.clone(), 5*NEAR);
+        contract.fund_treasury();
+        let transfer = TreasuryTransfer { receiver_id: acc1.clone(), amount: U128(5*NEAR), token_id: None };
+        set_context(acc1.clone(), 10*NEAR);
+        let kind = ProposalKind::Transfer(transfer);
+        contract.create_proposal("Should we pay harry?".to_string(), executing_rules(), Some(kind), None, None);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        contract.close_proposal(1);
+    }
+
+    #[test]
+    fn test_proposal_without_ballots_does_not_pass() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1.clone(), 10*NEAR);
+        // an even split passes by default, but 0 to 0 is not a split
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+        set_context(acc1, 0);
+        assert!(!contract.close_proposal(1));
+        assert_eq!(contract.get_proposal_status(1), Some(ProposalStatus::Rejected));
+    }
+
+    #[test]
+    fn test_events_logged() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        set_context(acc1.clone(), 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+        assert_eq!(
+            get_logs(),
+            vec![concat!(
+                r#"EVENT_JSON:{"standard":"nvoter","version":"1.0.0","event":"proposal_created","#,
+                r#""data":[{"proposal_id":"1","owner_id":"harry.near","title":"Should bears be legal pets?","#,
+                r#""kind":"yes_no"}]}"#
+            )]
+        );
+        set_context(acc2.clone(), 0);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        contract.vote_on_proposal(1, VoteChoice::No, None);
+        assert_eq!(
+            get_logs(),
+            vec![
+                concat!(
+                    r#"EVENT_JSON:{"standard":"nvoter","version":"1.0.0","event":"vote_cast","#,
+                    r#""data":[{"proposal_id":"1","voter_id":"mikky.near","ballot":"yes","weight":"1"}]}"#
+                ),
+                concat!(
+                    r#"EVENT_JSON:{"standard":"nvoter","version":"1.0.0","event":"vote_changed","#,
+                    r#""data":[{"proposal_id":"1","voter_id":"mikky.near","old_ballot":"yes","new_ballot":"no","#,
+                    r#""weight":"1"}]}"#
+                ),
+            ]
+        );
+        set_context(acc1.clone(), 0);
+        assert!(!contract.close_proposal(1));
+        // logged once the bond is settled
+        assert_eq!(
+            get_logs().last().unwrap(),
+            concat!(
+                r#"EVENT_JSON:{"standard":"nvoter","version":"1.0.0","event":"proposal_closed","#,
+                r#""data":[{"proposal_id":"1","outcome":"rejected","tally":["0","1","0"],"winning_option":null}]}"#
+            )
+        );
+        set_context(acc1, 10*NEAR);
+        contract.create_proposal("Should owls deliver mail?".to_string(), None, None, None, None);
+        assert!(contract.void_proposal(2));
+        assert_eq!(
+            get_logs().last().unwrap(),
+            concat!(
+                r#"EVENT_JSON:{"standard":"nvoter","version":"1.0.0","event":"proposal_voided","#,
+                r#""data":[{"proposal_id":"2","voided_by":"harry.near"}]}"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_upgrade_proposal() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        let code = vec![7u8; 1000];
+        set_callback_context();
+        contract.add_member(acc1.clone(), Role::Council);
+        set_context(acc1.clone(), 0);
+        let available_before = contract.storage_balance_of(acc1.clone()).unwrap().available.0;
+        let code_hash = contract.store_code(Base64VecU8(code.clone()));
+        assert_eq!(code_hash, Base58CryptoHash::from(env::sha256_array(&code)));
+        assert!(contract.has_code(code_hash));
+        let available_after = contract.storage_balance_of(acc1.clone()).unwrap().available.0;
+        assert!(available_before - available_after >= 1000 * env::storage_byte_cost());
+
+        set_context(acc1.clone(), 10*NEAR);
+        contract.create_proposal(
+            "Should the contract be upgraded?".to_string(),
+            executing_rules(),
+            Some(ProposalKind::Upgrade { code_hash }),
+            None,
+            None,
+        );
+        set_context(acc2, 0);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        set_context_at(acc1.clone(), 0, VOTING_PERIOD);
+        assert!(contract.close_proposal(1));
+        assert!(get_logs().contains(&"Upgrading the Contract for Proposal 1".to_string()));
+        assert_eq!(contract.get_proposal_status(1), Some(ProposalStatus::Passed));
+
+        // a failed migration leaves the proposal passed, to be retried
+        set_callback_result(PromiseResult::Failed);
+        assert_eq!(contract.on_actions_executed(1, 0, 1), ActionStatus::Failed);
+        assert_eq!(contract.get_proposal_status(1), Some(ProposalStatus::Passed));
+        set_callback_context();
+        contract.retry_upgrade(1);
+        assert_eq!(contract.get_action_statuses(1), Some(vec![ActionStatus::Pending]));
+        set_callback_result(PromiseResult::Successful(Vec::new()));
+        assert_eq!(contract.on_actions_executed(1, 0, 1), ActionStatus::Succeeded);
+        assert_eq!(contract.get_proposal_status(1), Some(ProposalStatus::Executed));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the Admin can Retry an Upgrade")]
+    fn test_upgrade_retried_by_admin_only() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        set_callback_context();
+        contract.add_member(acc1.clone(), Role::Council);
+        set_context(acc1.clone(), 10*NEAR);
+        let code_hash = contract.store_code(Base64VecU8(vec![7u8; 1000]));
+        let kind = ProposalKind::Upgrade { code_hash };
+        contract.create_proposal("Should the contract be upgraded?".to_string(), executing_rules(), Some(kind), None, None);
+        set_context(acc2.clone(), 0);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        set_context_at(acc1, 0, VOTING_PERIOD);
+        assert!(contract.close_proposal(1));
+        set_callback_result(PromiseResult::Failed);
+        contract.on_actions_executed(1, 0, 1);
+        set_context(acc2, 0);
+        contract.retry_upgrade(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the Admin or the Council can Propose an Upgrade")]
+    fn test_upgrade_proposed_by_council_only() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1, 10*NEAR);
+        let code_hash = contract.store_code(Base64VecU8(vec![7u8; 1000]));
+        let kind = ProposalKind::Upgrade { code_hash };
+        contract.create_proposal("Should the contract be upgraded?".to_string(), executing_rules(), Some(kind), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Code is not Stored; call store_code First")]
+    fn test_upgrade_proposal_needs_stored_code() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_callback_context();
+        contract.add_member(acc1.clone(), Role::Council);
+        set_context(acc1, 10*NEAR);
+        let code_hash = Base58CryptoHash::from(env::sha256_array(b"never stored"));
+        contract.create_proposal(
+            "Should the contract be upgraded?".to_string(),
+            executing_rules(),
+            Some(ProposalKind::Upgrade { code_hash }),
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    fn test_proposal_status_lifecycle() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        let rules = ProposalRules { voting_period: Some(U64(100)), ..Default::default() };
+        set_context_at(acc1.clone(), 10*NEAR, 1000);
+        contract.create_proposal("Should bears be legal pets?".to_string(), Some(rules), None, None, Some(true));
+        contract.create_proposal("Should owls deliver mail?".to_string(), None, None, None, None);
+        assert_eq!(contract.get_proposal_status(1), Some(ProposalStatus::Draft));
+        assert_eq!(contract.get_proposals_by_status(ProposalStatus::Open), vec![2]);
+
+        // the voting window of a draft starts when it is published
+        set_context_at(acc1.clone(), 0, 5000);
+        contract.publish_proposal(1);
+        assert_eq!(contract.get_proposal_status(1), Some(ProposalStatus::Open));
+        assert_eq!(contract.get_proposal_rules(1).unwrap().voting_ends_at, Some(U64(5100)));
+        set_context_at(acc2, 0, 5050);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        set_context_at(acc1, 0, 5050);
+        assert!(contract.close_proposal(1));
+        assert_eq!(contract.get_proposal_status(1), Some(ProposalStatus::Passed));
+        assert_eq!(contract.get_proposals_by_status(ProposalStatus::Passed), vec![1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Proposal is a Draft; Publish it First")]
+    fn test_draft_proposal_takes_no_ballots() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        set_context(acc1, 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, Some(true));
+        set_context(acc2, 0);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Proposal has Already Closed!")]
+    fn test_closed_proposal_cannot_be_voided() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1, 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+        contract.close_proposal(1);
+        contract.void_proposal(1);
+    }
+
+    #[test]
+    fn test_cancel_proposal_before_ballots() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1.clone(), 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+        contract.create_proposal("Should owls deliver mail?".to_string(), None, None, None, Some(true));
+        contract.cancel_proposal(1);
+        contract.cancel_proposal(2);
+        assert_eq!(contract.get_proposal_status(1), Some(ProposalStatus::Cancelled));
+        assert_eq!(contract.get_proposal_status(2), Some(ProposalStatus::Cancelled));
+        assert_eq!(contract.get_proposal_bond(1).unwrap().status, BondStatus::Refunded);
+        assert_eq!(contract.get_cancelled_proposal_count(), 2);
+        assert_eq!(contract.get_rejected_proposal_count(), 0);
+        assert_eq!(contract.get_voided_proposal_count(), 0);
+        assert!(get_logs().contains(
+            &concat!(
+                r#"EVENT_JSON:{"standard":"nvoter","version":"1.0.0","event":"proposal_cancelled","#,
+                r#""data":[{"proposal_id":"2","cancelled_by":"harry.near"}]}"#
+            )
+            .to_string()
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "A Proposal with Ballots needs Admin or Council Approval to be Cancelled")]
+    fn test_cancel_proposal_with_ballots_needs_approval() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        set_context(acc1.clone(), 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+        set_context(acc2, 0);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        set_context(acc1, 0);
+        contract.cancel_proposal(1);
+    }
+
+    #[test]
+    fn test_cancel_proposal_with_approvals() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        let acc3: AccountId = "kurt.near".parse().unwrap();
+        let acc4: AccountId = "lucy.near".parse().unwrap();
+        set_callback_context();
+        contract.add_member(acc3.clone(), Role::Council);
+        contract.add_member(acc4.clone(), Role::Council);
+        contract.set_cancel_approvals_needed(2);
+        set_context(acc1.clone(), 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+        contract.create_proposal("Should owls deliver mail?".to_string(), None, None, None, None);
+        set_context(acc2, 0);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        contract.vote_on_proposal(2, VoteChoice::Yes, None);
+
+        // two council members approve the first cancellation
+        set_context(acc3.clone(), 0);
+        contract.approve_cancellation(1);
+        set_context(acc4.clone(), 0);
+        contract.approve_cancellation(1);
+        assert_eq!(contract.get_cancel_approvals(1), vec![acc3, acc4]);
+        set_context(acc1.clone(), 0);
+        contract.cancel_proposal(1);
+        assert_eq!(contract.get_cancel_approvals(1), Vec::<AccountId>::new());
+
+        // the admin alone can approve the second
+        set_callback_context();
+        contract.approve_cancellation(2);
+        set_context(acc1, 0);
+        contract.cancel_proposal(2);
+        assert_eq!(contract.get_cancelled_proposal_count(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the Admin or the Council can Approve a Cancellation")]
+    fn test_cancel_approval_needs_council() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        set_context(acc1, 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+        set_context(acc2, 0);
+        contract.approve_cancellation(1);
+    }
+
+    #[test]
+    fn test_edit_proposal_keeps_revisions() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        set_context_at(acc1.clone(), 10*NEAR, 1000);
+        contract.create_proposal("Should bares be legal pets?".to_string(), None, None, None, None);
+        set_context_at(acc1.clone(), 0, 2000);
+        let metadata = ProposalMetadata { body: "Only the friendly ones.".to_string(), ..Default::default() };
+        contract.edit_proposal(1, "Should bears be legal pets?".to_string(), Some(metadata));
+
+        let revisions = contract.get_proposal_revisions(1);
+        assert_eq!(revisions.len(), 2);
+        assert_eq!(revisions[0].title, "Should bares be legal pets?");
+        assert_eq!(revisions[0].revised_at, U64(1000));
+        assert_eq!(revisions[1].revision, 1);
+        assert_eq!(revisions[1].body, "Only the friendly ones.");
+        assert_eq!(revisions[1].author, acc1);
+        assert_eq!(revisions[1].revised_at, U64(2000));
+
+        set_context(acc2.clone(), 0);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        assert_eq!(contract.get_vote(1, acc2).unwrap().revision, 1);
+    }
+
+    #[test]
+    fn test_edit_proposal_title_keeps_metadata() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1, 10*NEAR);
+        let metadata = ProposalMetadata {
+            body: "Only the friendly ones.".to_string(),
+            link: Some("https://forum.example/bears".to_string()),
+            tags: vec!["pets".to_string()],
+            content_hash: Some(Base64VecU8(vec![7; 32])),
+        };
+        contract.create_proposal("Should bares be legal pets?".to_string(), None, None, Some(metadata.clone()), None);
+        contract.edit_proposal(1, "Should bears be legal pets?".to_string(), None);
+
+        let proposal = contract.get_proposal(1).unwrap();
+        assert_eq!(proposal.title, "Should bears be legal pets?");
+        assert_eq!(proposal.body, metadata.body);
+        assert_eq!(proposal.link, metadata.link);
+        assert_eq!(proposal.tags, metadata.tags);
+        assert_eq!(proposal.content_hash, metadata.content_hash);
+    }
+
+    #[test]
+    #[should_panic(expected = "Proposal has Ballots; Change it through an Amendment Proposal")]
+    fn test_edit_proposal_with_ballots() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        set_context(acc1.clone(), 10*NEAR);
+        contract.create_proposal("Should bares be legal pets?".to_string(), None, None, None, None);
+        set_context(acc2, 0);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        set_context(acc1, 0);
+        contract.edit_proposal(1, "Should bears be legal pets?".to_string(), None);
+    }
+
+    #[test]
+    fn test_amendment_proposal() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near", "kurt.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        let acc3: AccountId = "kurt.near".parse().unwrap();
+        set_context(acc1.clone(), 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+        set_context(acc2.clone(), 0);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+
+        let kind = ProposalKind::Amendment {
+            proposal_id: 1,
+            proposal_text: "Should small bears be legal pets?".to_string(),
+            metadata: ProposalMetadata::default(),
+        };
+        set_context(acc3.clone(), 10*NEAR);
+        contract.create_proposal("Limit the bears to small ones".to_string(), None, Some(kind), None, None);
+        set_context(acc2.clone(), 0);
+        contract.vote_on_proposal(2, VoteChoice::Yes, None);
+        set_context(acc3.clone(), 0);
+        assert!(contract.close_proposal(2));
+        assert_eq!(contract.get_proposal_status(2), Some(ProposalStatus::Executed));
+
+        let proposal = contract.get_proposal(1).unwrap();
+        assert_eq!(proposal.title, "Should small bears be legal pets?");
+        assert_eq!(proposal.revision, 1);
+        assert_eq!(proposal.revised_by, acc3);
+        // the earlier ballot still shows the text it was cast on
+        let vote = contract.get_vote(1, acc2).unwrap();
+        assert_eq!(contract.get_proposal_revisions(1)[vote.revision as usize].title, "Should bears be legal pets?");
+    }
+
+    #[test]
+    #[should_panic(expected = "Proposal has Already Closed!")]
+    fn test_amendment_needs_open_proposal() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1, 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+        contract.close_proposal(1);
+        let kind = ProposalKind::Amendment {
+            proposal_id: 1,
+            proposal_text: "Should small bears be legal pets?".to_string(),
+            metadata: ProposalMetadata::default(),
+        };
+        contract.create_proposal("Limit the bears to small ones".to_string(), None, Some(kind), None, None);
+    }
+
+    #[test]
+    fn test_abstain_counts_for_quorum_not_majority() {
+        let mut contract = registered_contract(&["harry.near", "mikky.near", "kurt.near", "lucy.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        let acc3: AccountId = "kurt.near".parse().unwrap();
+        let acc4: AccountId = "lucy.near".parse().unwrap();
+        let rules = ProposalRules {
+            quorum: Some(Quorum::MinBallots(4)),
+            threshold: Threshold::Ratio { numerator: 2, denominator: 3 },
+            ..Default::default()
+        };
+        set_context(acc1.clone(), 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), Some(rules), None, None, None);
+        contract.vote_on_proposal(1, VoteChoice::No, None);
+        set_context(acc2, 0);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        set_context(acc3, 0);
+        contract.vote_on_proposal(1, VoteChoice::Yes, None);
+        set_context(acc4.clone(), 0);
+        contract.vote_on_proposal(1, VoteChoice::Abstain, None);
+        assert_eq!(contract.get_tally(1), amounts(&[2, 1, 1]));
+        assert_eq!(contract.get_vote(1, acc4).unwrap().ballot, Ballot::YesNo(VoteChoice::Abstain));
+
+        // four ballots meet the quorum, and two of the three voters taking a side reach two thirds
+        set_context(acc1, 0);
+        assert!(contract.close_proposal(1));
+        assert_eq!(contract.get_proposal_result(1).unwrap().tally, amounts(&[2, 1, 1]));
+    }
+
+    #[test]
+    fn test_ballots_stored_as_bool_decode_unchanged() {
+        // a yes/no ballot used to hold a bool: variant 0, then 1 for yes or 0 for no
+        assert_eq!(Ballot::try_from_slice(&[0, 1]).unwrap(), Ballot::YesNo(VoteChoice::Yes));
+        assert_eq!(Ballot::try_from_slice(&[0, 0]).unwrap(), Ballot::YesNo(VoteChoice::No));
+        assert_eq!(Ballot::YesNo(VoteChoice::Yes).try_to_vec().unwrap(), vec![0, 1]);
+        assert_eq!(Ballot::YesNo(VoteChoice::No).try_to_vec().unwrap(), vec![0, 0]);
+    }
+
+    #[test]
+    fn test_migrate_first_layout() {
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        let acc2: AccountId = "mikky.near".parse().unwrap();
+        let acc3: AccountId = "kurt.near".parse().unwrap();
+        set_context(acc1.clone(), 0);
+        env::state_write(&migration::ContractV0 {
+            proposal_count: 2,
+            successful_proposal_count: 1,
+            rejected_proposal_count: 0,
+            proposal_vals: BTreeMap::from([
+                (1, "Should bears be legal pets?".to_string()),
+                (2, "Should owls deliver mail?".to_string()),
+            ]),
+            proposal_owners: BTreeMap::from([(1, acc1.clone()), (2, acc2.clone())]),
+            proposal_votes: BTreeMap::from([
+                (1, vec![(acc2.clone(), true), (acc3.clone(), false)]),
+                (2, vec![(acc1.clone(), false), (acc1.clone(), true)]),
+            ]),
+            proposal_fate: BTreeMap::from([(1, true)]),
+        });
+
+        set_callback_context();
+        let mut contract = Contract::migrate();
+        assert_eq!(contract.get_proposal_count(), 2);
+        assert_eq!(contract.get_successful_proposal_count(), 1);
+        assert_eq!(contract.get_all_proposals()[&2], "Should owls deliver mail?");
+        let proposal = contract.get_proposal(1).unwrap();
+        assert_eq!(proposal.owner, acc1);
+        assert_eq!(proposal.created_at, U64(0));
+        let mut votes = contract.get_all_votes(1);
+        votes.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(votes, vec![(acc3.clone(), Ballot::YesNo(VoteChoice::No)), (acc2.clone(), Ballot::YesNo(VoteChoice::Yes))]);
+        // Repeated ballots of the first layout collapse to the last one
+        assert_eq!(contract.get_all_votes(2), vec![(acc1.clone(), Ballot::YesNo(VoteChoice::Yes))]);
+        assert_eq!(contract.get_proposal_outcome(1), Some(ProposalOutcome::Passed));
+        assert_eq!(contract.get_proposal_status(1), Some(ProposalStatus::Passed));
+        assert_eq!(contract.get_proposal_status(2), Some(ProposalStatus::Open));
+
+        set_context(acc3, NEAR);
+        contract.storage_deposit(None, None);
+        contract.vote_on_proposal(2, VoteChoice::No, None);
+        set_context(acc2, 0);
+        assert!(contract.close_proposal(2));
+        assert_eq!(contract.get_successful_proposal_count(), 2);
+        env::state_write(&contract);
+        assert_eq!(&env::storage_read(b"STATE").unwrap()[..5], b"nvst\x01");
+    }
+
+    #[test]
+    fn test_migrate_current_layout() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1.clone(), 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+        env::state_write(&contract);
+        drop(contract);
+
+        assert_eq!(&env::storage_read(b"STATE").unwrap()[..5], b"nvst\x01");
+
+        set_callback_context();
+        let contract = Contract::migrate();
+        assert_eq!(get_logs(), vec!["Contract State is Already Current"]);
+        assert_eq!(contract.get_proposal_count(), 1);
+        assert_eq!(contract.get_proposal(1).unwrap().owner, acc1);
+    }
+
+    #[test]
+    fn test_void_proposal() {
+        let mut contract = registered_contract(&["harry.near"]);
+        let acc1: AccountId = "harry.near".parse().unwrap();
+        set_context(acc1.clone(), 10*NEAR);
+        contract.create_proposal("Should bears be legal pets?".to_string(), None, None, None, None);
+        
+        set_context(acc1.clone(), 10*NEAR);
+        let result = contract.void_proposal(1);
+        assert!(result);
+        assert_eq!(contract.get_proposal_bond(1).unwrap().status, BondStatus::Slashed);
+        assert_eq!(contract.get_proposal_status(1), Some(ProposalStatus::Voided));
+        assert_eq!(contract.get_voided_proposal_count(), 1);
+        assert_eq!(contract.get_rejected_proposal_count(), 0);
+    }
+
+    
+
+    fn amounts(values: &[u128]) -> Vec<U128> {
+        values.iter().copied().map(U128).collect()
+    }
+
+    fn vendor_poll() -> ProposalKind {
+        ProposalKind::MultipleChoice {
+            options: vec!["Acme".to_string(), "Globex".to_string(), "Initech".to_string()],
+        }
+    }
+
+    // Rules for a proposal that executes, which must wait out its voting period
+    fn executing_rules() -> Option<ProposalRules> {
+        Some(ProposalRules { voting_period: Some(U64(VOTING_PERIOD)), ..Default::default() })
+    }
+
+    // A fresh contract with storage registered for each of the given accounts
+    fn registered_contract(account_ids: &[&str]) -> Contract {
+        let mut contract = Contract::default();
+        for account_id in account_ids {
+            set_context(account_id.parse().unwrap(), NEAR);
+            contract.storage_deposit(None, None);
+        }
+        contract
+    }
+
+    fn set_context(predecessor: AccountId, amount: Balance) {
+        let mut builder = VMContextBuilder::new();
+        
+        builder.predecessor_account_id(predecessor);
+        builder.attached_deposit(amount);
+    
+        testing_env!(builder.build());
+      }
+
+    fn set_callback_context() {
+        let mut builder = VMContextBuilder::new();
+
+        builder.predecessor_account_id(env::current_account_id());
+
+        testing_env!(builder.build());
+    }
+
+    fn set_callback_result(result: PromiseResult) {
+        let mut builder = VMContextBuilder::new();
+
+        builder.predecessor_account_id(env::current_account_id());
+
+        testing_env!(
+            builder.build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result]
+        );
+    }
+
+    fn set_context_at(predecessor: AccountId, amount: Balance, timestamp: u64) {
+        let mut builder = VMContextBuilder::new();
+
+        builder.predecessor_account_id(predecessor);
+        builder.attached_deposit(amount);
+        builder.block_timestamp(timestamp);
+
+        testing_env!(builder.build());
+    }
 }
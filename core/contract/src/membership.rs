@@ -0,0 +1,155 @@
+// Member registry and the role-based permissions checked by the contract methods
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, log, near_bindgen, AccountId};
+
+use crate::{Contract, ContractExt};
+
+// Role a registered member holds
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Council,
+    Member,
+    Observer,
+}
+
+// Action a role may be allowed to take
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    Propose,
+    Vote,
+    Close,
+    Void,
+    Moderate,
+}
+
+impl Role {
+    // Permissions a role carries until the admin sets them
+    fn default_permissions(self) -> Vec<Permission> {
+        match self {
+            Role::Council => vec![
+                Permission::Propose,
+                Permission::Vote,
+                Permission::Close,
+                Permission::Void,
+                Permission::Moderate,
+            ],
+            Role::Member => vec![Permission::Propose, Permission::Vote, Permission::Close],
+            Role::Observer => Vec::new(),
+        }
+    }
+}
+
+// Permissions of accounts outside the registry until the admin sets them,
+// so a contract without members stays open to everyone but moderation
+pub(crate) fn default_public_permissions() -> Vec<Permission> {
+    vec![Permission::Propose, Permission::Vote, Permission::Close, Permission::Void]
+}
+
+#[near_bindgen]
+impl Contract {
+    // Public method - returns the account managing the member registry
+    pub fn get_admin(&self) -> AccountId {
+        self.admin.clone()
+    }
+
+    // Public method - get the role of an account, if it is a member
+    pub fn get_member_role(&self, account_id: AccountId) -> Option<Role> {
+        self.members.get(&account_id).copied()
+    }
+
+    // Public method - get all members holding the given role
+    pub fn get_members_by_role(&self, role: Role) -> Vec<AccountId> {
+        self.members
+            .iter()
+            .filter(|(_, member_role)| **member_role == role)
+            .map(|(account_id, _)| account_id.clone())
+            .collect()
+    }
+
+    // Public method - get the permissions a role carries
+    pub fn get_role_permissions(&self, role: Role) -> Vec<Permission> {
+        self.role_permissions.get(&role).cloned().unwrap_or_else(|| role.default_permissions())
+    }
+
+    // Public method - get the permissions of accounts that are not members
+    pub fn get_public_permissions(&self) -> Vec<Permission> {
+        self.public_permissions.clone()
+    }
+
+    // Public method - hands the registry over to a new admin
+    pub fn set_admin(&mut self, account_id: AccountId) {
+        self.assert_admin();
+        log!("Registry Admin is now {}", account_id);
+        self.admin = account_id;
+    }
+
+    // Public method - registers an account with a role, or changes its role
+    pub fn add_member(&mut self, account_id: AccountId, role: Role) {
+        self.assert_admin();
+        log!("Registering {} as {:?}", account_id, role);
+        self.members.insert(account_id, role);
+    }
+
+    // Public method - removes an account from the registry
+    pub fn remove_member(&mut self, account_id: AccountId) {
+        self.assert_admin();
+        assert!(self.members.remove(&account_id).is_some(), "Account is not a Member");
+        log!("Removing Member {}", account_id);
+    }
+
+    // Public method - sets what members holding a role may do
+    pub fn set_role_permissions(&mut self, role: Role, permissions: Vec<Permission>) {
+        self.assert_admin();
+        self.role_permissions.insert(role, permissions);
+    }
+
+    // Public method - sets what accounts outside the registry may do
+    pub fn set_public_permissions(&mut self, permissions: Vec<Permission>) {
+        self.assert_admin();
+        self.public_permissions = permissions;
+    }
+}
+
+// Internal helpers, not exposed as contract methods
+impl Contract {
+    // The registry is managed by the admin, or by governance through the
+    // contract calling itself
+    pub(crate) fn assert_admin(&self) {
+        assert!(
+            self.is_admin(&env::predecessor_account_id()),
+            "Only the Admin or Governance can Manage Members"
+        );
+    }
+
+    // Whether the account is the admin, or the contract acting on a proposal
+    pub(crate) fn is_admin(&self, account_id: &AccountId) -> bool {
+        *account_id == self.admin || *account_id == env::current_account_id()
+    }
+
+    // Whether the account is the admin or sits on the council
+    pub(crate) fn is_council_or_admin(&self, account_id: &AccountId) -> bool {
+        self.is_admin(account_id) || self.members.get(account_id) == Some(&Role::Council)
+    }
+
+    // Returns the caller once it is permitted to take the action
+    pub(crate) fn assert_permitted(&self, permission: Permission) -> AccountId {
+        let caller = env::predecessor_account_id();
+        assert!(self.is_permitted(&caller, permission), "Account is not Permitted to {:?}", permission);
+        caller
+    }
+
+    // Whether the account's role, or the public permissions if it is not a
+    // member, allow the action
+    pub(crate) fn is_permitted(&self, account_id: &AccountId, permission: Permission) -> bool {
+        match self.members.get(account_id) {
+            Some(role) => self.get_role_permissions(*role).contains(&permission),
+            None => self.public_permissions.contains(&permission),
+        }
+    }
+}
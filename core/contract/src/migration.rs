@@ -0,0 +1,177 @@
+// Upgrades of the deployed contract state. The state opens with a tag naming
+// the version of its layout, and every earlier layout is frozen here, so
+// migrate() can convert whichever one the deployed contract wrote, one
+// version at a time. State without the tag is the first deployed layout.
+//
+// When the Contract layout changes, freeze the current one as a new version
+// below, bump CURRENT_STATE_VERSION and convert the frozen layout in
+// VersionedContract::next(). A change to a type stored in a collection, such
+// as Proposal, Vote or Ballot, is a layout change too: freeze the old type
+// and rewrite the stored values in the conversion, or they fail to decode.
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::store::UnorderedMap;
+use near_sdk::{env, log, near_bindgen, AccountId};
+use std::collections::BTreeMap;
+use std::io;
+
+use crate::{
+    Ballot, Contract, ContractExt, Proposal, ProposalKind, ProposalOutcome, ProposalRules, ProposalStatus,
+    StorageKey, Vote,
+};
+
+// Key the contract state is stored under
+const STATE_KEY: &[u8] = b"STATE";
+
+// Bytes opening a tagged contract state. The first layout opens with the
+// proposal count instead, which would take billions of proposals to match.
+const STATE_TAG: [u8; 4] = *b"nvst";
+
+// Version of the layout this code reads and writes
+pub(crate) const CURRENT_STATE_VERSION: u8 = 1;
+
+// The tag opening the contract state: STATE_TAG, then the layout version
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct StateVersion(u8);
+
+impl Default for StateVersion {
+    fn default() -> Self {
+        StateVersion(CURRENT_STATE_VERSION)
+    }
+}
+
+impl BorshSerialize for StateVersion {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&STATE_TAG)?;
+        writer.write_all(&[self.0])
+    }
+}
+
+impl BorshDeserialize for StateVersion {
+    fn deserialize(buf: &mut &[u8]) -> io::Result<Self> {
+        let tag = <[u8; 4]>::deserialize(buf)?;
+        if tag != STATE_TAG {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Contract State has no Version Tag"));
+        }
+        Ok(StateVersion(u8::deserialize(buf)?))
+    }
+}
+
+// Layout of the first deployed contract, which kept everything in one struct
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct ContractV0 {
+    pub(crate) proposal_count: u128,
+    pub(crate) successful_proposal_count: u128,
+    pub(crate) rejected_proposal_count: u128,
+    pub(crate) proposal_vals: BTreeMap<u128, String>,
+    pub(crate) proposal_owners: BTreeMap<u128, AccountId>,
+    pub(crate) proposal_votes: BTreeMap<u128, Vec<(AccountId, bool)>>,
+    pub(crate) proposal_fate: BTreeMap<u128, bool>,
+}
+
+// The contract state in any of the layouts it has had
+pub(crate) enum VersionedContract {
+    V0(ContractV0),
+    Current(Box<Contract>),
+}
+
+impl VersionedContract {
+    // Reads the stored state with the layout its tag names, or as the first
+    // layout when it has no tag
+    pub(crate) fn read() -> Self {
+        let state = env::storage_read(STATE_KEY).unwrap_or_else(|| env::panic_str("No Contract State to Migrate"));
+        if let Ok(StateVersion(version)) = StateVersion::deserialize(&mut state.as_slice()) {
+            return match version {
+                CURRENT_STATE_VERSION => VersionedContract::Current(Box::new(
+                    Contract::try_from_slice(&state)
+                        .unwrap_or_else(|_| env::panic_str("Contract State does not Match its Version")),
+                )),
+                _ => env::panic_str(&format!("Contract State has Unknown Version {}", version)),
+            };
+        }
+        ContractV0::try_from_slice(&state)
+            .map(VersionedContract::V0)
+            .unwrap_or_else(|_| env::panic_str("Contract State has an Unknown Layout"))
+    }
+
+    // The state converted to the next layout
+    fn next(self) -> Self {
+        match self {
+            VersionedContract::V0(old) => {
+                log!("Migrating {} Proposals from the First State Layout", old.proposal_count);
+                VersionedContract::Current(Box::new(old.into()))
+            }
+            current @ VersionedContract::Current(_) => current,
+        }
+    }
+}
+
+impl From<ContractV0> for Contract {
+    // Proposals of the first layout become yes/no proposals under the default
+    // rules. Their creation time was never recorded and is left at 0. That
+    // layout let accounts vote more than once; only the last ballot is kept.
+    fn from(old: ContractV0) -> Self {
+        let mut contract = Contract {
+            proposal_count: old.proposal_count,
+            successful_proposal_count: old.successful_proposal_count,
+            rejected_proposal_count: old.rejected_proposal_count,
+            ..Default::default()
+        };
+        for (proposal_id, title) in old.proposal_vals {
+            let proposal = Proposal {
+                title,
+                body: String::new(),
+                link: None,
+                tags: Vec::new(),
+                content_hash: None,
+                owner: old.proposal_owners[&proposal_id].clone(),
+                created_at_block: U64(0),
+                created_at: U64(0),
+                revision: 0,
+                revised_by: old.proposal_owners[&proposal_id].clone(),
+                revised_at: U64(0),
+            };
+            contract.proposals.insert(proposal_id, proposal);
+            let mut ballots = UnorderedMap::new(StorageKey::Ballots { proposal_id });
+            for (voter, vote_choice) in old.proposal_votes.get(&proposal_id).into_iter().flatten() {
+                ballots.insert(voter.clone(), Vote { ballot: Ballot::YesNo((*vote_choice).into()), weight: U128(1), revision: 0 });
+            }
+            contract.proposal_votes.insert(proposal_id, ballots);
+            contract.proposal_rules.insert(proposal_id, ProposalRules::default());
+            contract.proposal_kinds.insert(proposal_id, ProposalKind::YesNo);
+            contract.proposal_statuses.insert(proposal_id, ProposalStatus::Open);
+        }
+        // voided proposals were stored as rejected and stay that way
+        for (proposal_id, passed) in old.proposal_fate {
+            let (outcome, status) = if passed {
+                (ProposalOutcome::Passed, ProposalStatus::Passed)
+            } else {
+                (ProposalOutcome::Rejected, ProposalStatus::Rejected)
+            };
+            contract.proposal_fate.insert(proposal_id, outcome);
+            contract.proposal_statuses.insert(proposal_id, status);
+        }
+        contract
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    // Upgrade method - converts the stored state to the current layout.
+    // Call it in the same transaction that deploys the new code; state that
+    // is already current is kept as it is.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let mut state = VersionedContract::read();
+        if let VersionedContract::Current(_) = state {
+            log!("Contract State is Already Current");
+        }
+        loop {
+            state = match state {
+                VersionedContract::Current(contract) => return *contract,
+                older => older.next(),
+            };
+        }
+    }
+}
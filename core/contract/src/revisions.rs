@@ -0,0 +1,132 @@
+// Revisions of a proposal's text. The owner may edit a proposal freely until
+// the first ballot; after that only a passed amendment proposal changes it.
+// Replaced versions are kept, and every ballot records the revision it was
+// cast on, so voters can see exactly what they voted for.
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{Base64VecU8, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, log, near_bindgen, AccountId};
+
+use crate::{Contract, ContractExt, Permission, Proposal, ProposalKind, ProposalMetadata, ProposalStatus};
+
+// One version of a proposal's text, with who wrote it and when
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProposalRevision {
+    pub revision: u32,
+    pub title: String,
+    pub body: String,
+    pub link: Option<String>,
+    pub tags: Vec<String>,
+    pub content_hash: Option<Base64VecU8>,
+    pub author: AccountId,
+    // Nanoseconds since the Unix epoch
+    pub revised_at: U64,
+}
+
+impl Proposal {
+    // The proposal's current text as a revision
+    fn current_revision(&self) -> ProposalRevision {
+        ProposalRevision {
+            revision: self.revision,
+            title: self.title.clone(),
+            body: self.body.clone(),
+            link: self.link.clone(),
+            tags: self.tags.clone(),
+            content_hash: self.content_hash.clone(),
+            author: self.revised_by.clone(),
+            revised_at: self.revised_at,
+        }
+    }
+
+    // The proposal's current metadata
+    fn metadata(&self) -> ProposalMetadata {
+        ProposalMetadata {
+            body: self.body.clone(),
+            link: self.link.clone(),
+            tags: self.tags.clone(),
+            content_hash: self.content_hash.clone(),
+        }
+    }
+}
+
+// Panics unless the text would make a valid proposal
+pub(crate) fn validate_text(proposal_text: &str, metadata: &ProposalMetadata) {
+    assert!(!proposal_text.trim().is_empty(), "Proposal needs a Title");
+    if let Some(content_hash) = &metadata.content_hash {
+        assert_eq!(content_hash.0.len(), 32, "Content Hash must be a SHA-256 Hash");
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    // Public method - get every revision of a proposal, oldest first and the current one last
+    pub fn get_proposal_revisions(&self, proposal_id: u128) -> Vec<ProposalRevision> {
+        let Some(proposal) = self.proposals.get(&proposal_id) else { return Vec::new() };
+        let mut revisions = self.proposal_revisions.get(&proposal_id).cloned().unwrap_or_default();
+        revisions.push(proposal.current_revision());
+        revisions
+    }
+
+    // Public method - lets the owner replace the text and metadata of a
+    // draft or open proposal that has no ballots yet; without new metadata
+    // the current one is kept. The bytes the new revision takes up are
+    // charged to the owner's storage balance.
+    pub fn edit_proposal(&mut self, proposal_id: u128, proposal_text: String, metadata: Option<ProposalMetadata>) {
+        assert!(!self.status_of(proposal_id).is_closed(), "Proposal has Already Closed!");
+        let owner = self.assert_permitted(Permission::Propose);
+        assert_eq!(&owner, &self.proposals[&proposal_id].owner);
+        assert!(
+            !self.has_ballots(proposal_id),
+            "Proposal has Ballots; Change it through an Amendment Proposal"
+        );
+        let metadata = metadata.unwrap_or_else(|| self.proposals[&proposal_id].metadata());
+        validate_text(&proposal_text, &metadata);
+        let usage_before = env::storage_usage();
+        self.revise(proposal_id, proposal_text, metadata, owner.clone());
+        self.charge_storage(&owner, proposal_id, usage_before);
+    }
+}
+
+// Internal helpers, not exposed as contract methods
+impl Contract {
+    // Panics unless an amendment of the proposal may be put to a vote
+    pub(crate) fn assert_amendable(&self, proposal_id: u128) {
+        self.assert_open(proposal_id);
+        assert!(
+            !matches!(self.proposal_kinds[&proposal_id], ProposalKind::Amendment { .. }),
+            "Amendments cannot be Amended"
+        );
+    }
+
+    // Applies a passed amendment to its proposal, if that is still open
+    pub(crate) fn apply_amendment(&mut self, amendment_id: u128) {
+        let ProposalKind::Amendment { proposal_id, proposal_text, metadata } =
+            self.proposal_kinds[&amendment_id].clone()
+        else {
+            return;
+        };
+        if self.status_of(proposal_id) != ProposalStatus::Open {
+            log!("Proposal {} has Closed; Amendment {} is not Applied", proposal_id, amendment_id);
+            return;
+        }
+        log!("Amendment {} Revises Proposal {}", amendment_id, proposal_id);
+        let author = self.proposals[&amendment_id].owner.clone();
+        self.revise(proposal_id, proposal_text, metadata, author);
+        self.set_status(amendment_id, ProposalStatus::Executed);
+    }
+
+    // Keeps the current text of the proposal as a revision and replaces it
+    fn revise(&mut self, proposal_id: u128, proposal_text: String, metadata: ProposalMetadata, author: AccountId) {
+        let proposal = self.proposals.get_mut(&proposal_id).unwrap();
+        self.proposal_revisions.entry(proposal_id).or_default().push(proposal.current_revision());
+        proposal.revision += 1;
+        proposal.title = proposal_text;
+        proposal.body = metadata.body;
+        proposal.link = metadata.link;
+        proposal.tags = metadata.tags;
+        proposal.content_hash = metadata.content_hash;
+        proposal.revised_by = author;
+        proposal.revised_at = U64(env::block_timestamp());
+    }
+}
@@ -0,0 +1,87 @@
+// Instant-runoff counting for ranked-choice proposals
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+
+// One counting round of an instant-runoff vote
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RunoffRound {
+    // Weight counting towards each option this round; 0 once eliminated
+    pub totals: Vec<U128>,
+    // Option dropped at the end of this round, if counting went on
+    pub eliminated: Option<u32>,
+}
+
+// Runs instant-runoff rounds over the weighted rankings until one option holds
+// a majority of the weight still in play, or the remaining options are all
+// tied. Each ballot counts for its highest-ranked option not yet eliminated;
+// a ballot whose ranked options are all eliminated drops out. When several
+// options share the lowest total, the latest listed of them is eliminated.
+pub(crate) fn instant_runoff(option_count: usize, rankings: &[(&Vec<u32>, u128)]) -> Vec<RunoffRound> {
+    let mut active = vec![true; option_count];
+    let mut rounds = Vec::new();
+    loop {
+        let mut totals = vec![0u128; option_count];
+        for (ranking, weight) in rankings {
+            if let Some(option) = ranking.iter().find(|option| active[**option as usize]) {
+                totals[*option as usize] += weight;
+            }
+        }
+        let in_play: u128 = totals.iter().sum();
+        let remaining: Vec<usize> = (0..option_count).filter(|option| active[*option]).collect();
+        let top = remaining.iter().map(|option| totals[*option]).max().unwrap_or(0);
+        let bottom = remaining.iter().map(|option| totals[*option]).min().unwrap_or(0);
+        let round_totals = totals.iter().copied().map(U128).collect();
+        if in_play == 0 || 2 * top > in_play || top == bottom {
+            rounds.push(RunoffRound { totals: round_totals, eliminated: None });
+            return rounds;
+        }
+        let eliminated = *remaining.iter().rev().find(|option| totals[**option] == bottom).unwrap();
+        active[eliminated] = false;
+        rounds.push(RunoffRound { totals: round_totals, eliminated: Some(eliminated as u32) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amounts(values: &[u128]) -> Vec<U128> {
+        values.iter().copied().map(U128).collect()
+    }
+
+    #[test]
+    fn test_majority_in_first_round() {
+        let ballots = [vec![0, 1], vec![0], vec![1, 0]];
+        let rankings: Vec<(&Vec<u32>, u128)> = ballots.iter().map(|ranking| (ranking, 1)).collect();
+        let rounds = instant_runoff(3, &rankings);
+        assert_eq!(rounds, vec![RunoffRound { totals: amounts(&[2, 1, 0]), eliminated: None }]);
+    }
+
+    #[test]
+    fn test_transfers_after_elimination() {
+        // option 2 is eliminated first and its ballots move to option 1
+        let ballots = [vec![0], vec![0], vec![1], vec![1], vec![2, 1]];
+        let rankings: Vec<(&Vec<u32>, u128)> = ballots.iter().map(|ranking| (ranking, 1)).collect();
+        let rounds = instant_runoff(3, &rankings);
+        assert_eq!(
+            rounds,
+            vec![
+                RunoffRound { totals: amounts(&[2, 2, 1]), eliminated: Some(2) },
+                RunoffRound { totals: amounts(&[2, 3, 0]), eliminated: None },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_exhausted_ballots_drop_out() {
+        let ballots = [vec![0], vec![0], vec![1], vec![1], vec![2]];
+        let rankings: Vec<(&Vec<u32>, u128)> = ballots.iter().map(|ranking| (ranking, 1)).collect();
+        let rounds = instant_runoff(3, &rankings);
+        assert_eq!(
+            rounds.last(),
+            Some(&RunoffRound { totals: amounts(&[2, 2, 0]), eliminated: None })
+        );
+    }
+}
@@ -0,0 +1,168 @@
+// NEP-145 storage management: accounts deposit NEAR up front and are charged
+// for the bytes their proposals and ballots add to the contract state
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, log, near_bindgen, AccountId, Balance, Promise};
+
+use crate::{Contract, ContractExt};
+
+// Bytes charged for registering an account: the trie overhead of its
+// storage record plus the longest possible account ID and the record itself
+const STORAGE_ACCOUNT_BYTES: u64 = 40 + 1 + 4 + 64 + 16 + 8;
+
+// An account's storage deposit and the bytes it is charged for
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct StorageAccount {
+    deposit: Balance,
+    bytes: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalance {
+    pub total: U128,
+    pub available: U128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalanceBounds {
+    pub min: U128,
+    pub max: Option<U128>,
+}
+
+impl StorageAccount {
+    fn balance(&self) -> StorageBalance {
+        let locked = Balance::from(self.bytes) * env::storage_byte_cost();
+        StorageBalance { total: U128(self.deposit), available: U128(self.deposit.saturating_sub(locked)) }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    // Public method - registers an account, or tops up its storage balance.
+    // With registration_only, anything above the minimum balance is refunded.
+    #[payable]
+    pub fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        let amount = env::attached_deposit();
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let registration_only = registration_only.unwrap_or(false);
+        let min = self.storage_balance_bounds().min.0;
+        let refund = match self.storage_accounts.get_mut(&account_id) {
+            Some(_) if registration_only => amount,
+            Some(account) => {
+                account.deposit += amount;
+                0
+            }
+            None => {
+                assert!(amount >= min, "Deposit at least {} yoctoNEAR to Register Storage", min);
+                let deposit = if registration_only { min } else { amount };
+                log!("Registering Storage for {}", account_id);
+                let account = StorageAccount { deposit, bytes: STORAGE_ACCOUNT_BYTES };
+                self.storage_accounts.insert(account_id.clone(), account);
+                amount - deposit
+            }
+        };
+        if refund > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+        self.storage_accounts[&account_id].balance()
+    }
+
+    // Public method - withdraws storage balance not covering any stored bytes,
+    // all of it unless an amount is given. Requires exactly 1 yoctoNEAR.
+    #[payable]
+    pub fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let account = self
+            .storage_accounts
+            .get_mut(&account_id)
+            .unwrap_or_else(|| env::panic_str("Account has no Storage Balance"));
+        let available = account.balance().available.0;
+        let amount = amount.map_or(available, |amount| amount.0);
+        assert!(amount <= available, "Not Enough Available Storage Balance");
+        account.deposit -= amount;
+        let balance = account.balance();
+        if amount > 0 {
+            Promise::new(account_id).transfer(amount);
+        }
+        balance
+    }
+
+    // Public method - get the storage balance of an account, if it registered
+    pub fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.storage_accounts.get(&account_id).map(StorageAccount::balance)
+    }
+
+    // Public method - get the smallest deposit that registers an account; there is no maximum
+    pub fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        let min = Balance::from(STORAGE_ACCOUNT_BYTES) * env::storage_byte_cost();
+        StorageBalanceBounds { min: U128(min), max: None }
+    }
+}
+
+// Internal helpers, not exposed as contract methods
+impl Contract {
+    // Panics unless the account has registered storage to be charged against
+    pub(crate) fn assert_storage_registered(&self, account_id: &AccountId) {
+        assert!(
+            self.storage_accounts.contains_key(account_id),
+            "Account has no Storage Balance; call storage_deposit First"
+        );
+    }
+
+    // Charges the account for the bytes added to the contract state since
+    // usage_before, or credits it for the bytes freed. Collection writes are
+    // cached, so the state is flushed before storage usage is measured.
+    pub(crate) fn charge_storage(&mut self, account_id: &AccountId, proposal_id: u128, usage_before: u64) {
+        self.flush_state(proposal_id);
+        let usage_after = env::storage_usage();
+        let account = self
+            .storage_accounts
+            .get_mut(account_id)
+            .unwrap_or_else(|| env::panic_str("Account has no Storage Balance; call storage_deposit First"));
+        if usage_after >= usage_before {
+            account.bytes += usage_after - usage_before;
+        } else {
+            account.bytes = account.bytes.saturating_sub(usage_before - usage_after);
+        }
+        let needed = Balance::from(account.bytes) * env::storage_byte_cost();
+        assert!(
+            needed <= account.deposit,
+            "Not Enough Storage Balance: {} yoctoNEAR Needed, {} Deposited", needed, account.deposit
+        );
+    }
+
+    // Writes every cached change to the contract state, including the
+    // ballots and commitments of the given proposal
+    fn flush_state(&mut self, proposal_id: u128) {
+        if let Some(ballots) = self.proposal_votes.get_mut(&proposal_id) {
+            ballots.flush();
+        }
+        if let Some(commits) = self.proposal_commits.get_mut(&proposal_id) {
+            commits.flush();
+        }
+        self.proposals.flush();
+        self.proposal_votes.flush();
+        self.proposal_commits.flush();
+        self.proposal_rules.flush();
+        self.proposal_kinds.flush();
+        self.proposal_statuses.flush();
+        self.proposal_revisions.flush();
+        self.proposal_bonds.flush();
+        self.stakes.flush();
+        self.proposal_stakes.flush();
+        self.account_stakes.flush();
+        self.credits_spent.flush();
+        self.pending_transfers.flush();
+        self.action_deposits.flush();
+        self.delegations.flush();
+        self.delegators.flush();
+    }
+}
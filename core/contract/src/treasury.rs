@@ -0,0 +1,191 @@
+// The DAO treasury: NEAR and registered NEP-141 tokens paid out by transfer
+// proposals, and the NEAR attached to the actions of executable proposals.
+// An open proposal commits what it would pay, so the proposals open at any
+// time can never promise more than the treasury holds.
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, log, near_bindgen, AccountId, Balance, Gas, Promise, PromiseOrValue, PromiseResult};
+
+use crate::{ext_ft, Contract, ContractExt, ProposalAction, ProposalStatus};
+
+// Gas for paying out tokens and for returning a failed payout to the treasury
+const GAS_FOR_FT_TRANSFER: Gas = Gas(10_000_000_000_000);
+const GAS_FOR_ON_TREASURY_PAYOUT: Gas = Gas(5_000_000_000_000);
+
+// A payout from the treasury, in NEAR unless a token is given
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TreasuryTransfer {
+    pub receiver_id: AccountId,
+    pub amount: U128,
+    pub token_id: Option<AccountId>,
+}
+
+// What the treasury holds of one asset, and how much of it open proposals have committed
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub(crate) struct AssetBalance {
+    balance: Balance,
+    committed: Balance,
+}
+
+// One asset of the treasury; NEAR has no token ID
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TreasuryBalance {
+    pub token_id: Option<AccountId>,
+    pub balance: U128,
+    pub committed: U128,
+}
+
+impl AssetBalance {
+    fn view(&self, token_id: Option<AccountId>) -> TreasuryBalance {
+        TreasuryBalance { token_id, balance: U128(self.balance), committed: U128(self.committed) }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    // Public method - get the balance and committed amount of NEAR and of each registered token
+    pub fn get_treasury(&self) -> Vec<TreasuryBalance> {
+        let mut balances = vec![self.treasury_near.view(None)];
+        balances.extend(self.treasury_tokens.iter().map(|(token_id, asset)| asset.view(Some(token_id.clone()))));
+        balances
+    }
+
+    // Public method - get the transfers committed by open proposals, by proposal ID
+    pub fn get_pending_transfers(&self) -> Vec<(u128, TreasuryTransfer)> {
+        self.pending_transfers.iter().map(|(id, transfer)| (*id, transfer.clone())).collect()
+    }
+
+    // Public method - adds the attached NEAR to the treasury
+    #[payable]
+    pub fn fund_treasury(&mut self) {
+        let amount = env::attached_deposit();
+        assert!(amount > 0, "Attach NEAR to Fund the Treasury");
+        log!("{} Funds the Treasury with {}", env::predecessor_account_id(), amount);
+        self.add_to_treasury(amount);
+    }
+
+    // Public method - lets the treasury accept a NEP-141 token
+    pub fn register_treasury_token(&mut self, token_id: AccountId) {
+        self.assert_admin();
+        log!("Treasury Accepts Token {}", token_id);
+        self.treasury_tokens.entry(token_id).or_default();
+    }
+
+    // NEP-141 receiver - adds tokens sent with ft_transfer_call to the treasury.
+    // Tokens that are not registered are refunded.
+    pub fn ft_on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> PromiseOrValue<U128> {
+        let token_id = env::predecessor_account_id();
+        let Some(asset) = self.treasury_tokens.get_mut(&token_id) else {
+            return PromiseOrValue::Value(amount);
+        };
+        log!("{} Funds the Treasury with {} of {}", sender_id, amount.0, token_id);
+        if !msg.is_empty() {
+            log!("Transfer Message: {}", msg);
+        }
+        asset.balance += amount.0;
+        PromiseOrValue::Value(U128(0))
+    }
+
+    // Callback - marks the proposal executed, or returns the amount to the
+    // treasury if the payout failed
+    #[private]
+    pub fn on_treasury_payout(&mut self, proposal_id: u128, transfer: TreasuryTransfer) -> bool {
+        if let PromiseResult::Successful(_) = env::promise_result(0) {
+            self.set_status(proposal_id, ProposalStatus::Executed);
+            return true;
+        }
+        log!("Transfer of Proposal {} Failed, Returning {} to the Treasury", proposal_id, transfer.amount.0);
+        self.asset_mut(&transfer.token_id).balance += transfer.amount.0;
+        false
+    }
+}
+
+// Internal helpers, not exposed as contract methods
+impl Contract {
+    fn asset_mut(&mut self, token_id: &Option<AccountId>) -> &mut AssetBalance {
+        match token_id {
+            None => &mut self.treasury_near,
+            Some(token_id) => self
+                .treasury_tokens
+                .get_mut(token_id)
+                .unwrap_or_else(|| env::panic_str("Token is not Registered with the Treasury")),
+        }
+    }
+
+    // Adds NEAR the contract already holds, such as a slashed bond, to the treasury
+    pub(crate) fn add_to_treasury(&mut self, amount: Balance) {
+        self.treasury_near.balance += amount;
+    }
+
+    // Sets the transfer's amount aside until its proposal is decided
+    pub(crate) fn commit_transfer(&mut self, proposal_id: u128, transfer: &TreasuryTransfer) {
+        assert!(transfer.amount.0 > 0, "Transfer Amount must be Positive");
+        let asset = self.asset_mut(&transfer.token_id);
+        assert!(
+            asset.committed + transfer.amount.0 <= asset.balance,
+            "Treasury cannot Cover the Transfer: {} Available",
+            asset.balance - asset.committed
+        );
+        asset.committed += transfer.amount.0;
+        self.pending_transfers.insert(proposal_id, transfer.clone());
+    }
+
+    // Sets the NEAR attached to the actions aside until their proposal is decided
+    pub(crate) fn commit_action_deposits(&mut self, proposal_id: u128, actions: &[ProposalAction]) {
+        let total: Balance = actions.iter().map(|action| action.deposit.0).sum();
+        if total == 0 {
+            return;
+        }
+        let asset = &mut self.treasury_near;
+        assert!(
+            asset.committed + total <= asset.balance,
+            "Treasury cannot Cover the Action Deposits: {} Available",
+            asset.balance - asset.committed
+        );
+        asset.committed += total;
+        self.action_deposits.insert(proposal_id, total);
+    }
+
+    // Releases the amounts committed by a proposal that did not pass
+    pub(crate) fn release_commitments(&mut self, proposal_id: u128) {
+        if let Some(transfer) = self.pending_transfers.remove(&proposal_id) {
+            self.asset_mut(&transfer.token_id).committed -= transfer.amount.0;
+        }
+        if let Some(total) = self.action_deposits.remove(&proposal_id) {
+            self.treasury_near.committed -= total;
+        }
+    }
+
+    // Takes the NEAR committed to the actions of a passed proposal out of the
+    // treasury, as they are about to attach it
+    pub(crate) fn spend_action_deposits(&mut self, proposal_id: u128) {
+        if let Some(total) = self.action_deposits.remove(&proposal_id) {
+            self.treasury_near.committed -= total;
+            self.treasury_near.balance -= total;
+        }
+    }
+
+    // Pays out the amount committed by a passed proposal
+    pub(crate) fn execute_transfer(&mut self, proposal_id: u128) {
+        let Some(transfer) = self.pending_transfers.remove(&proposal_id) else { return };
+        let asset = self.asset_mut(&transfer.token_id);
+        asset.committed -= transfer.amount.0;
+        asset.balance -= transfer.amount.0;
+        log!("Paying {} to {} for Proposal {}", transfer.amount.0, transfer.receiver_id, proposal_id);
+        let payout = match &transfer.token_id {
+            None => Promise::new(transfer.receiver_id.clone()).transfer(transfer.amount.0),
+            Some(token_id) => ext_ft::ext(token_id.clone())
+                .with_attached_deposit(1)
+                .with_static_gas(GAS_FOR_FT_TRANSFER)
+                .ft_transfer(transfer.receiver_id.clone(), transfer.amount, None),
+        };
+        payout.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_ON_TREASURY_PAYOUT)
+                .on_treasury_payout(proposal_id, transfer),
+        );
+    }
+}
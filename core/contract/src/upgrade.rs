@@ -0,0 +1,87 @@
+// Upgrades of the contract code, decided by upgrade proposals, which only the
+// admin and the council may create. The new code is stored first and
+// proposals refer to it by hash; once one passes, the
+// contract deploys the code to itself and migrates its state. The outcome is
+// recorded by on_actions_executed, so the new code must keep that callback.
+use near_sdk::json_types::{Base58CryptoHash, Base64VecU8};
+use near_sdk::{env, log, near_bindgen, AccountId, CryptoHash, Gas, Promise};
+
+use crate::actions::GAS_FOR_ON_ACTIONS_EXECUTED;
+use crate::{ActionStatus, Contract, ContractExt, Permission, ProposalKind, ProposalStatus};
+
+// Gas for migrating the state once the new code is deployed
+const GAS_FOR_MIGRATE: Gas = Gas(100_000_000_000_000);
+
+#[near_bindgen]
+impl Contract {
+    // Public method - whether code with the given hash is stored for upgrade proposals
+    pub fn has_code(&self, code_hash: Base58CryptoHash) -> bool {
+        self.stored_code.contains_key(&CryptoHash::from(code_hash))
+    }
+
+    // Public method - stores contract code for upgrade proposals to refer to by
+    // its hash, which is returned. The bytes are charged to the caller's
+    // storage balance.
+    pub fn store_code(&mut self, code: Base64VecU8) -> Base58CryptoHash {
+        let uploader = self.assert_permitted(Permission::Propose);
+        let code_hash: CryptoHash = env::sha256_array(&code.0);
+        if !self.stored_code.contains_key(&code_hash) {
+            let usage_before = env::storage_usage();
+            log!("{} Stores {} Bytes of Code", uploader, code.0.len());
+            self.stored_code.insert(code_hash, code.0);
+            self.stored_code.flush();
+            // proposal IDs start at 1, so no ballots are flushed
+            self.charge_storage(&uploader, 0, usage_before);
+        }
+        code_hash.into()
+    }
+
+    // Public method - lets the admin deploy the code of a passed upgrade
+    // proposal again after the upgrade failed
+    pub fn retry_upgrade(&mut self, proposal_id: u128) {
+        assert!(self.is_admin(&env::predecessor_account_id()), "Only the Admin can Retry an Upgrade");
+        let ProposalKind::Upgrade { code_hash } = self.proposal_kinds[&proposal_id].clone() else {
+            env::panic_str("Proposal is not an Upgrade")
+        };
+        assert!(
+            self.status_of(proposal_id) == ProposalStatus::Passed
+                && self.action_statuses.get(&proposal_id) == Some(&vec![ActionStatus::Failed]),
+            "Only a Failed Upgrade can be Retried"
+        );
+        self.execute_upgrade(proposal_id, &code_hash);
+    }
+}
+
+// Internal helpers, not exposed as contract methods
+impl Contract {
+    // Panics unless the proposer may propose an upgrade to the code, and the
+    // code has been stored
+    pub(crate) fn assert_upgrade_proposable(&self, proposer: &AccountId, code_hash: &Base58CryptoHash) {
+        assert!(
+            self.is_council_or_admin(proposer),
+            "Only the Admin or the Council can Propose an Upgrade"
+        );
+        assert!(
+            self.stored_code.contains_key(&CryptoHash::from(*code_hash)),
+            "Code is not Stored; call store_code First"
+        );
+    }
+
+    // Deploys the code of a passed upgrade proposal and migrates the state in
+    // the same promise, so the new code never runs on an unmigrated state. If
+    // the migration fails the deployment is reverted with it, and the
+    // callback records the upgrade as failed on the old code.
+    pub(crate) fn execute_upgrade(&mut self, proposal_id: u128, code_hash: &Base58CryptoHash) {
+        let code = self.stored_code[&CryptoHash::from(*code_hash)].clone();
+        log!("Upgrading the Contract for Proposal {}", proposal_id);
+        self.action_statuses.insert(proposal_id, vec![ActionStatus::Pending]);
+        Promise::new(env::current_account_id())
+            .deploy_contract(code)
+            .function_call("migrate".to_string(), Vec::new(), 0, GAS_FOR_MIGRATE)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_ON_ACTIONS_EXECUTED)
+                    .on_actions_executed(proposal_id, 0, 1),
+            );
+    }
+}
@@ -0,0 +1,30 @@
+// Minimal NEP-141 stand-in for the sandbox tests: balances are set directly
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::store::LookupMap;
+use near_sdk::{near_bindgen, AccountId};
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Contract {
+    balances: LookupMap<AccountId, u128>,
+}
+
+impl Default for Contract {
+    fn default() -> Self {
+        Self { balances: LookupMap::new(b"b") }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    // Test helper - overwrite an account's balance
+    pub fn set_balance(&mut self, account_id: AccountId, balance: U128) {
+        self.balances.insert(account_id, balance.0);
+    }
+
+    // NEP-141 view - the account's balance, 0 if it never held tokens
+    pub fn ft_balance_of(&self, account_id: AccountId) -> U128 {
+        U128(self.balances.get(&account_id).copied().unwrap_or(0))
+    }
+}
@@ -0,0 +1,27 @@
+// Receiver of proposal actions for the sandbox tests: records what it is sent
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{env, near_bindgen};
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct Contract {
+    messages: Vec<String>,
+}
+
+#[near_bindgen]
+impl Contract {
+    // Action target - keeps the message
+    pub fn record(&mut self, message: String) {
+        self.messages.push(message);
+    }
+
+    // Action target - always fails
+    pub fn fail(&mut self) {
+        env::panic_str("Receiver Refused the Action");
+    }
+
+    // Test helper - every message recorded so far
+    pub fn get_messages(&self) -> Vec<String> {
+        self.messages.clone()
+    }
+}
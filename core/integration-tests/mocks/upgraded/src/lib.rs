@@ -0,0 +1,56 @@
+// Upgrade target for the sandbox tests: keeps the proposal count of the
+// contract it replaces
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{env, log, near_bindgen};
+
+// Tag and layout version the replaced contract's state opens with
+const STATE_TAG: [u8; 4] = *b"nvst";
+const STATE_VERSION: u8 = 1;
+
+// The leading fields of the replaced contract's state; the rest is not read
+#[derive(BorshDeserialize)]
+struct ReplacedState {
+    tag: [u8; 4],
+    version: u8,
+    proposal_count: u128,
+}
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct Contract {
+    proposal_count: u128,
+}
+
+#[near_bindgen]
+impl Contract {
+    // Upgrade method - reads the proposal count out of the replaced state,
+    // once its tag shows the layout is the one expected
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let state = env::storage_read(b"STATE").unwrap_or_else(|| env::panic_str("No Contract State to Migrate"));
+        let replaced = ReplacedState::deserialize(&mut state.as_slice())
+            .unwrap_or_else(|_| env::panic_str("Contract State is too Short"));
+        assert!(
+            replaced.tag == STATE_TAG && replaced.version == STATE_VERSION,
+            "Contract State has an Unknown Layout"
+        );
+        Self { proposal_count: replaced.proposal_count }
+    }
+
+    // Callback - the replaced contract records the upgrade's outcome here
+    #[private]
+    pub fn on_actions_executed(&mut self, proposal_id: u128, first_action: u32, action_count: u32) {
+        log!("Actions {} to {} of Proposal {} Succeeded", first_action, first_action + action_count - 1, proposal_id);
+    }
+
+    // Test helper - the proposal count carried over by the upgrade
+    pub fn get_proposal_count(&self) -> u128 {
+        self.proposal_count
+    }
+
+    // Test helper - tells the upgraded code apart from the original
+    pub fn get_version(&self) -> String {
+        "upgraded".to_string()
+    }
+}
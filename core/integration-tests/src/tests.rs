@@ -1,7 +1,12 @@
 use std::{env, fs};
 use near_units::parse_near;
 use serde_json::json;
-use workspaces::{Account, Contract};
+use workspaces::network::Sandbox;
+use workspaces::operations::Function;
+use workspaces::{Account, Contract, Worker};
+
+// Voting window of the proposals that execute once passed, in nanoseconds
+const VOTING_PERIOD: &str = "1000000000";
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -20,47 +25,510 @@ async fn main() -> anyhow::Result<()> {
         .transact()
         .await?
         .into_result()?;
+    let bob = account
+        .create_subaccount( "bob")
+        .initial_balance(parse_near!("30 N"))
+        .transact()
+        .await?
+        .into_result()?;
+    let carol = account
+        .create_subaccount( "carol")
+        .initial_balance(parse_near!("30 N"))
+        .transact()
+        .await?
+        .into_result()?;
+
+    // keep the proposal bond small, as the tests create many proposals
+    contract.call("set_proposal_bond_amount")
+        .args_json(json!({"amount": parse_near!("0.1 N").to_string()}))
+        .transact()
+        .await?
+        .into_result()?;
+
+    // every account pays for the storage its proposals and ballots use
+    for account in [&alice, &bob, &carol] {
+        account.call(contract.id(), "storage_deposit")
+            .args_json(json!({}))
+            .deposit(parse_near!("1 N"))
+            .transact()
+            .await?
+            .into_result()?;
+    }
+
+    // deploy the mock NEP-141 token used for weighted voting
+    let ft_wasm = workspaces::compile_project("./mocks/fungible-token").await?;
+    let token = worker.dev_deploy(&ft_wasm).await?;
+
+    // deploy two mock receivers of proposal actions
+    let receiver_wasm = workspaces::compile_project("./mocks/receiver").await?;
+    let receiver = worker.dev_deploy(&receiver_wasm).await?;
+    let other_receiver = worker.dev_deploy(&receiver_wasm).await?;
+
+    // the first deployed version, upgraded to the current code by the migration
+    // test, and measured against the current code by the vote gas test
+    let v0_wasm = workspaces::compile_project("./mocks/contract-v0").await?;
+    let old_contract = worker.dev_deploy(&v0_wasm).await?;
+    let baseline_contract = worker.dev_deploy(&v0_wasm).await?;
+
+    // a fresh contract upgraded by vote to the mock upgrade target
+    let upgraded_wasm = workspaces::compile_project("./mocks/upgraded").await?;
+    let governed_contract = worker.dev_deploy(&wasm).await?;
 
     // begin tests
-    test_default_message(&alice, &contract).await?;
-    test_changes_message(&alice, &contract).await?;
+    test_create_and_close_proposal(&alice, &bob, &contract).await?;
+    test_vote_gas_is_independent_of_history(&alice, &bob, &carol, &contract, &baseline_contract).await?;
+    test_token_weighted_votes(&alice, &bob, &carol, &contract, &token).await?;
+    test_passed_proposal_runs_actions(&worker, &alice, &bob, &contract, &receiver, &other_receiver).await?;
+    test_migrate_first_layout(&alice, &bob, &carol, &old_contract, &wasm).await?;
+    test_passed_proposal_upgrades_contract(&worker, &alice, &bob, &governed_contract, &upgraded_wasm).await?;
+    Ok(())
+}
+
+async fn test_create_and_close_proposal(
+    owner: &Account,
+    voter: &Account,
+    contract: &Contract,
+) -> anyhow::Result<()> {
+    owner.call(contract.id(), "create_proposal")
+        .args_json(json!({"proposal_text": "Should bears be legal pets?"}))
+        .deposit(parse_near!("0.1 N"))
+        .transact()
+        .await?
+        .into_result()?;
+    let proposal_id: u128 = owner
+        .call(contract.id(), "get_proposal_count")
+        .args_json(json!({}))
+        .view()
+        .await?
+        .json()?;
+
+    // voting twice only replaces the earlier ballot
+    for vote_choice in ["no", "yes"] {
+        voter.call(contract.id(), "vote_on_proposal")
+            .args_json(json!({"proposal_id": proposal_id, "vote_choice": vote_choice}))
+            .transact()
+            .await?
+            .into_result()?;
+    }
+    let votes: Vec<(String, String)> = owner
+        .call(contract.id(), "get_all_votes")
+        .args_json(json!({"proposal_id": proposal_id}))
+        .view()
+        .await?
+        .json()?;
+    assert_eq!(votes, vec![(voter.id().to_string(), "yes".to_string())]);
+
+    let passed: bool = owner
+        .call(contract.id(), "close_proposal")
+        .args_json(json!({"proposal_id": proposal_id}))
+        .transact()
+        .await?
+        .json()?;
+    assert!(passed);
+    let bond: serde_json::Value = owner
+        .call(contract.id(), "get_proposal_bond")
+        .args_json(json!({"proposal_id": proposal_id}))
+        .view()
+        .await?
+        .json()?;
+    assert_eq!(bond["status"], "refunded");
+    let status: String = owner
+        .call(contract.id(), "get_proposal_status")
+        .args_json(json!({"proposal_id": proposal_id}))
+        .view()
+        .await?
+        .json()?;
+    assert_eq!(status, "passed");
+    println!("      Passed ✅ creates, votes on and closes a proposal");
+    Ok(())
+}
+
+async fn test_vote_gas_is_independent_of_history(
+    owner: &Account,
+    voter: &Account,
+    late_voter: &Account,
+    contract: &Contract,
+    baseline_contract: &Contract,
+) -> anyhow::Result<()> {
+    let (baseline_early, baseline_late) =
+        measure_vote_gas(owner, voter, late_voter, baseline_contract, 0, json!(true)).await?;
+    let (early_gas, late_gas) =
+        measure_vote_gas(owner, voter, late_voter, contract, parse_near!("0.1 N"), json!("yes")).await?;
+    println!("      vote gas with 2 proposals / with 27 proposals and 50 ballots");
+    println!("        first layout:   {} / {}", baseline_early, baseline_late);
+    println!("        current layout: {} / {}", early_gas, late_gas);
+    // With whole-state Borsh loading the second vote grew with every proposal
+    // and ballot stored; with per-proposal prefixes it stays within noise.
+    assert!(late_gas < early_gas + early_gas / 10);
+    assert!(late_gas.saturating_sub(early_gas) < baseline_late - baseline_early);
+    println!("      Passed ✅ vote gas does not grow with unrelated proposals");
     Ok(())
 }
 
-async fn test_default_message(
-    user: &Account,
+// Gas burnt by a vote on a young proposal, and by a second vote on it once
+// 25 unrelated proposals with two ballots each are stored
+async fn measure_vote_gas(
+    owner: &Account,
+    voter: &Account,
+    late_voter: &Account,
+    contract: &Contract,
+    bond: u128,
+    vote_choice: serde_json::Value,
+) -> anyhow::Result<(u64, u64)> {
+    owner.call(contract.id(), "create_proposal")
+        .args_json(json!({"proposal_text": "Should owls deliver mail?"}))
+        .deposit(bond)
+        .transact()
+        .await?
+        .into_result()?;
+    let proposal_id: u128 = owner
+        .call(contract.id(), "get_proposal_count")
+        .args_json(json!({}))
+        .view()
+        .await?
+        .json()?;
+    let early_vote = voter.call(contract.id(), "vote_on_proposal")
+        .args_json(json!({"proposal_id": proposal_id, "vote_choice": vote_choice}))
+        .transact()
+        .await?
+        .into_result()?;
+
+    // grow the contract state with unrelated proposals and ballots
+    for _ in 0..25 {
+        owner.call(contract.id(), "create_proposal")
+            .args_json(json!({"proposal_text": "Filler proposal to grow the contract state"}))
+            .deposit(bond)
+            .transact()
+            .await?
+            .into_result()?;
+        let filler_id: u128 = owner
+            .call(contract.id(), "get_proposal_count")
+            .args_json(json!({}))
+            .view()
+            .await?
+            .json()?;
+        for account in [owner, voter] {
+            account.call(contract.id(), "vote_on_proposal")
+                .args_json(json!({"proposal_id": filler_id, "vote_choice": vote_choice}))
+                .transact()
+                .await?
+                .into_result()?;
+        }
+    }
+
+    let late_vote = late_voter.call(contract.id(), "vote_on_proposal")
+        .args_json(json!({"proposal_id": proposal_id, "vote_choice": vote_choice}))
+        .transact()
+        .await?
+        .into_result()?;
+    Ok((early_vote.total_gas_burnt, late_vote.total_gas_burnt))
+}
+
+async fn test_token_weighted_votes(
+    whale: &Account,
+    minnow: &Account,
+    other_minnow: &Account,
     contract: &Contract,
+    token: &Contract,
 ) -> anyhow::Result<()> {
-    let message: String = user
-        .call( contract.id(), "get_greeting")
+    for (account, balance) in [(whale, "100"), (minnow, "30"), (other_minnow, "40")] {
+        token.call("set_balance")
+            .args_json(json!({"account_id": account.id(), "balance": balance}))
+            .transact()
+            .await?
+            .into_result()?;
+    }
+    whale.call(contract.id(), "create_proposal")
+        .args_json(json!({
+            "proposal_text": "Should token holders decide?",
+            "rules": {"weighting": {"token_balance": {"token_id": token.id()}}},
+        }))
+        .deposit(parse_near!("0.1 N"))
+        .transact()
+        .await?
+        .into_result()?;
+    let proposal_id: u128 = whale
+        .call(contract.id(), "get_proposal_count")
         .args_json(json!({}))
+        .view()
+        .await?
+        .json()?;
+
+    for (account, vote_choice) in [(whale, "yes"), (minnow, "no"), (other_minnow, "no")] {
+        account.call(contract.id(), "vote_on_proposal")
+            .args_json(json!({"proposal_id": proposal_id, "vote_choice": vote_choice}))
+            .max_gas()
+            .transact()
+            .await?
+            .into_result()?;
+    }
+    let tally: Vec<String> = whale
+        .call(contract.id(), "get_tally")
+        .args_json(json!({"proposal_id": proposal_id}))
+        .view()
+        .await?
+        .json()?;
+    assert_eq!(tally, vec!["100".to_string(), "70".to_string(), "0".to_string()]);
+
+    // one yes ballot outweighs two no ballots
+    let passed: bool = whale
+        .call(contract.id(), "close_proposal")
+        .args_json(json!({"proposal_id": proposal_id}))
+        .transact()
+        .await?
+        .json()?;
+    assert!(passed);
+    println!("      Passed ✅ weighs ballots by NEP-141 token balance");
+    Ok(())
+}
+
+async fn test_passed_proposal_runs_actions(
+    worker: &Worker<Sandbox>,
+    owner: &Account,
+    voter: &Account,
+    contract: &Contract,
+    receiver: &Contract,
+    other_receiver: &Contract,
+) -> anyhow::Result<()> {
+    // args are base64 JSON: {"message":"hello"}, {"message":"bye"} and {}
+    let actions = json!([
+        {
+            "receiver_id": receiver.id(),
+            "method_name": "record",
+            "args": "eyJtZXNzYWdlIjoiaGVsbG8ifQ==",
+            "deposit": "0",
+            "gas": "20000000000000",
+        },
+        {
+            "receiver_id": other_receiver.id(),
+            "method_name": "record",
+            "args": "eyJtZXNzYWdlIjoiYnllIn0=",
+            "deposit": "0",
+            "gas": "20000000000000",
+        },
+        {
+            "receiver_id": other_receiver.id(),
+            "method_name": "fail",
+            "args": "e30=",
+            "deposit": "0",
+            "gas": "20000000000000",
+        },
+    ]);
+    owner.call(contract.id(), "create_proposal")
+        .args_json(json!({
+            "proposal_text": "Should the receiver say hello?",
+            "rules": {"voting_period": VOTING_PERIOD},
+            "kind": {"actions": {"actions": actions}},
+        }))
+        .deposit(parse_near!("0.1 N"))
         .transact()
         .await?
+        .into_result()?;
+    let proposal_id: u128 = owner
+        .call(contract.id(), "get_proposal_count")
+        .args_json(json!({}))
+        .view()
+        .await?
         .json()?;
+    voter.call(contract.id(), "vote_on_proposal")
+        .args_json(json!({"proposal_id": proposal_id, "vote_choice": "yes"}))
+        .transact()
+        .await?
+        .into_result()?;
 
-    assert_eq!(message, "Hello".to_string());
-    println!("      Passed ✅ gets default message");
+    // the actions and their callbacks all run before the transaction completes
+    worker.fast_forward(10).await?;
+    owner.call(contract.id(), "close_proposal")
+        .args_json(json!({"proposal_id": proposal_id}))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+    let statuses: Vec<String> = owner
+        .call(contract.id(), "get_action_statuses")
+        .args_json(json!({"proposal_id": proposal_id}))
+        .view()
+        .await?
+        .json()?;
+    assert_eq!(statuses, vec!["succeeded".to_string(), "failed".to_string(), "failed".to_string()]);
+    let messages: Vec<String> = receiver
+        .view("get_messages", Vec::new())
+        .await?
+        .json()?;
+    assert_eq!(messages, vec!["hello".to_string()]);
+    // the actions on the other receiver ran as one batch, which failed as a whole
+    let messages: Vec<String> = other_receiver
+        .view("get_messages", Vec::new())
+        .await?
+        .json()?;
+    assert!(messages.is_empty());
+    println!("      Passed ✅ runs the actions of a passed proposal");
     Ok(())
 }
 
-async fn test_changes_message(
-    user: &Account,
+async fn test_migrate_first_layout(
+    owner: &Account,
+    voter: &Account,
+    late_voter: &Account,
     contract: &Contract,
+    wasm: &[u8],
 ) -> anyhow::Result<()> {
-    user.call(contract.id(), "set_greeting")
-        .args_json(json!({"message": "Howdy"}))
+    // write proposals and ballots with the first version of the contract
+    for proposal_text in ["Should bears be legal pets?", "Should owls deliver mail?"] {
+        owner.call(contract.id(), "create_proposal")
+            .args_json(json!({"proposal_text": proposal_text}))
+            .transact()
+            .await?
+            .into_result()?;
+    }
+    for (proposal_id, vote_choice) in [(1, true), (2, false)] {
+        voter.call(contract.id(), "vote_on_proposal")
+            .args_json(json!({"proposal_id": proposal_id, "vote_choice": vote_choice}))
+            .transact()
+            .await?
+            .into_result()?;
+    }
+    owner.call(contract.id(), "close_proposal")
+        .args_json(json!({"proposal_id": 1}))
         .transact()
         .await?
         .into_result()?;
 
-    let message: String = user
-        .call(contract.id(), "get_greeting")
+    // deploy the current code and migrate the state in one transaction
+    contract.batch()
+        .deploy(wasm)
+        .call(Function::new("migrate").args_json(json!({})))
+        .transact()
+        .await?
+        .into_result()?;
+
+    let proposal_count: u128 = owner
+        .call(contract.id(), "get_proposal_count")
+        .args_json(json!({}))
+        .view()
+        .await?
+        .json()?;
+    assert_eq!(proposal_count, 2);
+    let proposals: serde_json::Value = owner
+        .call(contract.id(), "get_all_proposals")
+        .args_json(json!({}))
+        .view()
+        .await?
+        .json()?;
+    assert_eq!(proposals, json!({"1": "Should bears be legal pets?", "2": "Should owls deliver mail?"}));
+    let proposal: serde_json::Value = owner
+        .call(contract.id(), "get_proposal")
+        .args_json(json!({"proposal_id": 2}))
+        .view()
+        .await?
+        .json()?;
+    assert_eq!(proposal["owner"], owner.id().to_string());
+    let votes: Vec<(String, String)> = owner
+        .call(contract.id(), "get_all_votes")
+        .args_json(json!({"proposal_id": 2}))
+        .view()
+        .await?
+        .json()?;
+    assert_eq!(votes, vec![(voter.id().to_string(), "no".to_string())]);
+    let outcome: serde_json::Value = owner
+        .call(contract.id(), "get_proposal_outcome")
+        .args_json(json!({"proposal_id": 1}))
+        .view()
+        .await?
+        .json()?;
+    assert_eq!(outcome, "passed");
+
+    // migrated proposals stay open to new ballots under the current rules
+    late_voter.call(contract.id(), "storage_deposit")
         .args_json(json!({}))
+        .deposit(parse_near!("1 N"))
+        .transact()
+        .await?
+        .into_result()?;
+    late_voter.call(contract.id(), "vote_on_proposal")
+        .args_json(json!({"proposal_id": 2, "vote_choice": "yes"}))
+        .transact()
+        .await?
+        .into_result()?;
+    let passed: bool = owner
+        .call(contract.id(), "close_proposal")
+        .args_json(json!({"proposal_id": 2}))
+        .transact()
+        .await?
+        .json()?;
+    assert!(passed);
+    println!("      Passed ✅ migrates proposals and ballots of the first deployed version");
+    Ok(())
+}
+
+async fn test_passed_proposal_upgrades_contract(
+    worker: &Worker<Sandbox>,
+    owner: &Account,
+    voter: &Account,
+    contract: &Contract,
+    upgraded_wasm: &[u8],
+) -> anyhow::Result<()> {
+    for account in [owner, voter] {
+        account.call(contract.id(), "storage_deposit")
+            .args_json(json!({}))
+            .deposit(parse_near!("2 N"))
+            .transact()
+            .await?
+            .into_result()?;
+    }
+    // only the admin and the council may propose upgrades
+    contract.call("add_member")
+        .args_json(json!({"account_id": owner.id(), "role": "council"}))
+        .transact()
+        .await?
+        .into_result()?;
+    let code_hash: String = owner
+        .call(contract.id(), "store_code")
+        .args_json(json!({"code": base64::encode(upgraded_wasm)}))
+        .max_gas()
+        .transact()
+        .await?
+        .json()?;
+    owner.call(contract.id(), "create_proposal")
+        .args_json(json!({
+            "proposal_text": "Should the contract be upgraded?",
+            "rules": {"voting_period": VOTING_PERIOD},
+            "kind": {"upgrade": {"code_hash": code_hash}},
+        }))
+        .deposit(parse_near!("1 N"))
+        .transact()
+        .await?
+        .into_result()?;
+    voter.call(contract.id(), "vote_on_proposal")
+        .args_json(json!({"proposal_id": 1, "vote_choice": "yes"}))
+        .transact()
+        .await?
+        .into_result()?;
+    worker.fast_forward(10).await?;
+    let passed: bool = owner
+        .call(contract.id(), "close_proposal")
+        .args_json(json!({"proposal_id": 1}))
+        .max_gas()
         .transact()
         .await?
         .json()?;
+    assert!(passed);
 
-    assert_eq!(message, "Howdy".to_string());
-    println!("      Passed ✅ changes message");
+    // the new code runs on the migrated state
+    let version: String = owner
+        .call(contract.id(), "get_version")
+        .args_json(json!({}))
+        .view()
+        .await?
+        .json()?;
+    assert_eq!(version, "upgraded");
+    let proposal_count: u128 = owner
+        .call(contract.id(), "get_proposal_count")
+        .args_json(json!({}))
+        .view()
+        .await?
+        .json()?;
+    assert_eq!(proposal_count, 1);
+    println!("      Passed ✅ upgrades the contract code once an upgrade proposal passes");
     Ok(())
-}
\ No newline at end of file
+}